@@ -305,6 +305,10 @@ export class ClaudeBridge extends EventEmitter {
         type: 'result',
         sessionId: this.sessionId,
         subtype: msg.subtype,
+        usage: msg.usage || null,
+        costUsd: msg.total_cost_usd ?? null,
+        durationMs: msg.duration_ms ?? null,
+        numTurns: msg.num_turns ?? null,
       });
     }
     // All other types silently dropped
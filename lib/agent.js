@@ -30,7 +30,7 @@ const RELAY_URL = 'wss://claude.iptinno.com';
 const RELAY_PUBLIC_URL = 'https://claude.iptinno.com';
 
 export async function startAgent(config) {
-  const { auth_token, port = 9680 } = config;
+  const { auth_token, port = 9680, device_id, device_name } = config;
 
   // Write PID so logout/status can find us
   savePid(process.pid);
@@ -42,7 +42,7 @@ export async function startAgent(config) {
   console.log(`[Agent] HTTP port: ${port}`);
 
   // Connect to relay server
-  const relayClient = new RelayClient(RELAY_URL, auth_token);
+  const relayClient = new RelayClient(RELAY_URL, auth_token, { deviceId: device_id, deviceName: device_name });
 
   // System tray (optional — works on Windows, skipped elsewhere)
   let tray;
@@ -112,6 +112,10 @@ export async function startAgent(config) {
           sessionManager.handleUserMessage(msg.sessionId, msg.content, true);
         } else if (msg.type === 'stop_message' && msg.sessionId) {
           sessionManager.abortSession(msg.sessionId);
+        } else if (msg.type === 'set_auto_accept' && msg.sessionId) {
+          sessionManager.handleSetAutoAccept(msg.sessionId, msg.autoAccept);
+        } else if (msg.type === 'dismiss_question' && msg.sessionId) {
+          sessionManager.handleDismissQuestion(msg.sessionId);
         }
       } catch {}
     });
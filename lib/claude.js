@@ -7,6 +7,7 @@ export class ClaudeBridge extends EventEmitter {
     this.projectPath = projectPath;
     this.sessionId = null;
     this.isRunning = false;
+    this.autoAccept = false;
     this.messageQueue = [];
     this._abortController = null;
     // Pending AskUserQuestion: { resolve, questions }
@@ -17,6 +18,21 @@ export class ClaudeBridge extends EventEmitter {
     this._seenToolIds = new Set();
   }
 
+  // Tools already run without prompts here; the flag is only kept in sync
+  // for clients that show it
+  setAutoAccept(value) {
+    this.autoAccept = value;
+  }
+
+  dismissQuestion() {
+    if (!this._pendingQuestion) return;
+    const { resolve } = this._pendingQuestion;
+    this._pendingQuestion = null;
+    resolve({ behavior: 'deny', message: 'User dismissed the question' });
+    this.emit('output', { type: 'question_answered' });
+    this.emit('status', 'processing');
+  }
+
   sendMessage(content) {
     // If waiting for a question answer, treat this as the answer
     if (this._pendingQuestion) {
@@ -231,6 +247,10 @@ export class ClaudeBridge extends EventEmitter {
         type: 'result',
         sessionId: this.sessionId,
         subtype: msg.subtype,
+        usage: msg.usage || null,
+        costUsd: msg.total_cost_usd ?? null,
+        durationMs: msg.duration_ms ?? null,
+        numTurns: msg.num_turns ?? null,
       });
     }
     // All other types silently dropped
@@ -1,4 +1,10 @@
 import express from 'express';
+import { readFileSync } from 'fs';
+import path from 'path';
+import { fileURLToPath } from 'url';
+
+const __dirname = path.dirname(fileURLToPath(import.meta.url));
+const pkg = JSON.parse(readFileSync(path.join(__dirname, '..', 'package.json'), 'utf-8'));
 
 export function createHttpServer(sessionManager, relayPublicUrl) {
   const app = express();
@@ -15,7 +21,15 @@ export function createHttpServer(sessionManager, relayPublicUrl) {
 
   // Health check
   app.get('/health', (req, res) => {
-    res.json({ status: 'ok', relayPublicUrl: relayPublicUrl || '' });
+    res.json({
+      status: 'ok',
+      relayPublicUrl: relayPublicUrl || '',
+      version: pkg.version,
+      relayConnected: sessionManager.relayClient.connected,
+      uptime: Math.round(process.uptime()),
+      lastError: sessionManager.relayClient.lastError,
+      authRejected: sessionManager.relayClient.authRejected,
+    });
   });
 
   // List active sessions
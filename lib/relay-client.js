@@ -2,18 +2,27 @@ import WebSocket from 'ws';
 import { EventEmitter } from 'events';
 
 export class RelayClient extends EventEmitter {
-  constructor(relayUrl, authToken) {
+  constructor(relayUrl, authToken, { deviceId, deviceName } = {}) {
     super();
     this.relayUrl = relayUrl;
     this.authToken = authToken;
+    this.deviceId = deviceId;
+    this.deviceName = deviceName;
     this.ws = null;
     this.reconnectDelay = 1000;
     this.maxReconnectDelay = 30000;
     this.shouldReconnect = true;
+    this.connected = false;
+    this.lastError = null;
+    this.authRejected = false;
   }
 
   connect() {
-    const url = `${this.relayUrl}/ws/agent?token=${encodeURIComponent(this.authToken)}`;
+    const params = new URLSearchParams({ token: this.authToken });
+    // Lets the relay show which machine a session comes from
+    if (this.deviceId) params.set('device_id', this.deviceId);
+    if (this.deviceName) params.set('device_name', this.deviceName);
+    const url = `${this.relayUrl}/ws/agent?${params}`;
     console.log(`[Relay] Connecting to ${this.relayUrl}...`);
 
     this.ws = new WebSocket(url);
@@ -21,6 +30,8 @@ export class RelayClient extends EventEmitter {
     this.ws.on('open', () => {
       console.log('[Relay] Connected');
       this.reconnectDelay = 1000;
+      this.connected = true;
+      this.lastError = null;
       this.emit('connected');
     });
 
@@ -35,12 +46,20 @@ export class RelayClient extends EventEmitter {
 
     this.ws.on('close', () => {
       console.log('[Relay] Disconnected');
+      this.connected = false;
       this.emit('disconnected');
       this.scheduleReconnect();
     });
 
     this.ws.on('error', (err) => {
       console.error('[Relay] Error:', err.message);
+      this.lastError = err.message;
+      // The token is no longer valid; retrying can't succeed until re-login
+      if (err.message.includes('Unexpected server response: 401')) {
+        this.authRejected = true;
+        this.shouldReconnect = false;
+        this.lastError = 'Relay rejected the auth token (401)';
+      }
     });
   }
 
@@ -63,13 +82,14 @@ export class RelayClient extends EventEmitter {
     }
   }
 
-  registerSession(sessionId, projectName, projectPath, sessionToken) {
+  registerSession(sessionId, projectName, projectPath, sessionToken, autoAccept) {
     this.send({
       type: 'session_register',
       sessionId,
       projectName,
       projectPath,
       sessionToken,
+      autoAccept: autoAccept ?? false,
     });
   }
 
@@ -14,6 +14,10 @@ export class SessionManager {
         this.handleUserMessage(msg.sessionId, msg.content, false);
       } else if (msg.type === 'stop_message') {
         this.abortSession(msg.sessionId);
+      } else if (msg.type === 'set_auto_accept') {
+        this.handleSetAutoAccept(msg.sessionId, msg.autoAccept);
+      } else if (msg.type === 'dismiss_question') {
+        this.handleDismissQuestion(msg.sessionId);
       }
     });
   }
@@ -46,6 +50,7 @@ export class SessionManager {
       projectName,
       claude,
       status: 'idle',
+      autoAccept: false,
     };
 
     // Wire up Claude events to both relay AND local VSCode clients
@@ -155,7 +160,7 @@ export class SessionManager {
   reshareSession(sessionId) {
     const session = this.sessions.get(sessionId);
     if (session) {
-      this.relayClient.registerSession(sessionId, session.projectName, session.projectPath, session.sessionToken);
+      this.relayClient.registerSession(sessionId, session.projectName, session.projectPath, session.sessionToken, session.autoAccept);
       console.log(`[Session] Re-shared: ${sessionId}`);
     }
   }
@@ -175,6 +180,24 @@ export class SessionManager {
     }
   }
 
+  handleDismissQuestion(sessionId) {
+    const session = this.sessions.get(sessionId);
+    if (!session) return;
+    session.claude.dismissQuestion();
+  }
+
+  handleSetAutoAccept(sessionId, autoAccept) {
+    const session = this.sessions.get(sessionId);
+    if (!session) return;
+    session.autoAccept = !!autoAccept;
+    session.claude.setAutoAccept(session.autoAccept);
+
+    // Broadcast the change to all clients for sync
+    const msg = { type: 'auto_accept_changed', sessionId, autoAccept: session.autoAccept };
+    this.relayClient.send(msg);
+    this.localBroadcast(msg);
+  }
+
   getSession(sessionId) {
     return this.sessions.get(sessionId);
   }
@@ -194,6 +217,7 @@ export class SessionManager {
         projectPath: session.projectPath,
         sessionToken: session.sessionToken,
         status: session.status,
+        autoAccept: session.autoAccept,
       });
     }
     return list;
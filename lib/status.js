@@ -14,10 +14,11 @@ export async function statusCommand() {
   }
 
   // Agent
+  const port = config.port || 9680;
   try {
-    const res = await fetch('http://127.0.0.1:9680/health', { signal: AbortSignal.timeout(3000) });
+    const res = await fetch(`http://127.0.0.1:${port}/health`, { signal: AbortSignal.timeout(3000) });
     const data = await res.json();
-    console.log(`Agent:    running (port 9680)`);
+    console.log(`Agent:    running (port ${port})`);
   } catch {
     console.log('Agent:    not running');
   }
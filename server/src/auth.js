@@ -23,10 +23,31 @@ export function validateToken(token) {
   return user;
 }
 
+/**
+ * Why a user with valid credentials may not sign in, or null.
+ */
+export function loginBlockReason(user) {
+  if (REQUIRE_EMAIL_VERIFICATION && !user.email_verified) return 'Email not verified';
+  if (REQUIRE_MODERATION && user.status === 'pending') return 'Account pending admin approval';
+  if (user.status === 'rejected') return 'Account has been rejected';
+  return null;
+}
+
 export function authMiddleware(req, res, next) {
   const token = req.headers.authorization?.replace('Bearer ', '');
   const user = validateToken(token);
   if (!user) {
+    // A valid token on a pending or rejected account is not a revoked one;
+    // clients keep the session and show the reason instead. The web app never
+    // sees this: it calls no route behind this middleware (pairing-code
+    // redeem and push check tokens themselves, sessions come over the relay).
+    const owner = token ? getUserByToken(token) : null;
+    if (owner) {
+      return res.status(403).json({
+        error: loginBlockReason(owner) || 'Account not approved',
+        code: 'account_blocked',
+      });
+    }
     return res.status(401).json({ error: 'Unauthorized' });
   }
   req.user = user;
@@ -38,8 +38,30 @@ export function initDb() {
       created_at TEXT DEFAULT (datetime('now')),
       FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
     );
+
+    CREATE TABLE IF NOT EXISTS devices (
+      id INTEGER PRIMARY KEY AUTOINCREMENT,
+      user_id INTEGER NOT NULL,
+      device_id TEXT NOT NULL,
+      name TEXT NOT NULL DEFAULT '',
+      auth_token TEXT UNIQUE NOT NULL,
+      created_at TEXT DEFAULT (datetime('now')),
+      last_seen_at TEXT DEFAULT (datetime('now')),
+      UNIQUE (user_id, device_id),
+      FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+    );
   `);
 
+  // Columns added after the initial schema
+  const userColumns = db.prepare('PRAGMA table_info(users)').all().map((c) => c.name);
+  if (!userColumns.includes('reset_code')) {
+    db.exec('ALTER TABLE users ADD COLUMN reset_code TEXT');
+    db.exec('ALTER TABLE users ADD COLUMN reset_code_expires TEXT');
+  }
+  if (!userColumns.includes('reset_attempts')) {
+    db.exec('ALTER TABLE users ADD COLUMN reset_attempts INTEGER NOT NULL DEFAULT 0');
+  }
+
   console.log('[DB] SQLite initialized');
   return db;
 }
@@ -59,7 +81,12 @@ export function getUserByEmail(email) {
 }
 
 export function getUserByToken(authToken) {
-  return db.prepare('SELECT * FROM users WHERE auth_token = ?').get(authToken) || null;
+  const user = db.prepare('SELECT * FROM users WHERE auth_token = ?').get(authToken);
+  if (user) return user;
+  // Per-device token; device_id/device_name tell which machine is calling
+  return db.prepare(
+    'SELECT u.*, d.device_id, d.name AS device_name FROM devices d JOIN users u ON u.id = d.user_id WHERE d.auth_token = ?'
+  ).get(authToken) || null;
 }
 
 export function getUserById(id) {
@@ -88,6 +115,69 @@ export function verifyEmail(userId) {
   ).run(userId);
 }
 
+// --- Password reset queries ---
+
+export function setResetCode(userId, code, expiresAt) {
+  db.prepare(
+    'UPDATE users SET reset_code = ?, reset_code_expires = ?, reset_attempts = 0 WHERE id = ?'
+  ).run(code, expiresAt, userId);
+}
+
+// Counts a wrong code; after `maxAttempts` the code stops working
+export function recordResetFailure(userId, maxAttempts) {
+  const { reset_attempts: attempts } = db.prepare(
+    'UPDATE users SET reset_attempts = reset_attempts + 1 WHERE id = ? RETURNING reset_attempts'
+  ).get(userId);
+  if (attempts >= maxAttempts) {
+    db.prepare('UPDATE users SET reset_code = NULL, reset_code_expires = NULL WHERE id = ?').run(userId);
+  }
+  return attempts;
+}
+
+export function resetPassword(userId, passwordHash, authToken) {
+  db.prepare(
+    'UPDATE users SET password_hash = ?, auth_token = ?, reset_code = NULL, reset_code_expires = NULL, reset_attempts = 0 WHERE id = ?'
+  ).run(passwordHash, authToken, userId);
+  db.prepare('DELETE FROM devices WHERE user_id = ?').run(userId);
+}
+
+// --- Device queries ---
+
+// Issues a fresh token for the device, replacing any previous one
+export function upsertDevice(userId, deviceId, name, authToken) {
+  db.prepare(`
+    INSERT INTO devices (user_id, device_id, name, auth_token)
+    VALUES (?, ?, ?, ?)
+    ON CONFLICT(user_id, device_id) DO UPDATE SET
+      name = excluded.name,
+      auth_token = excluded.auth_token,
+      last_seen_at = datetime('now')
+  `).run(userId, deviceId, name, authToken);
+}
+
+export function getDevicesByUserId(userId) {
+  return db.prepare(
+    'SELECT device_id, name, created_at, last_seen_at FROM devices WHERE user_id = ? ORDER BY last_seen_at DESC'
+  ).all(userId);
+}
+
+export function touchDevice(userId, deviceId) {
+  db.prepare("UPDATE devices SET last_seen_at = datetime('now') WHERE user_id = ? AND device_id = ?")
+    .run(userId, deviceId);
+}
+
+// Returns false if the user has no such device
+export function renameDevice(userId, deviceId, name) {
+  return db.prepare('UPDATE devices SET name = ? WHERE user_id = ? AND device_id = ?')
+    .run(name, userId, deviceId).changes > 0;
+}
+
+// Deleting the row invalidates the device's token
+export function deleteDevice(userId, deviceId) {
+  return db.prepare('DELETE FROM devices WHERE user_id = ? AND device_id = ?')
+    .run(userId, deviceId).changes > 0;
+}
+
 // --- Push subscription queries ---
 
 export function upsertPushSubscription(userId, subscription) {
@@ -0,0 +1,85 @@
+import crypto from 'crypto';
+
+// Device-authorization grant (RFC 8628 style): the desktop app gets a
+// device_code to poll with and a short user_code the user confirms in a browser.
+
+const CODE_TTL_MS = 10 * 60 * 1000;
+const POLL_INTERVAL_S = 5;
+// No vowels or look-alike digits, so codes can't spell words or be misread
+const USER_CODE_ALPHABET = 'BCDFGHJKLMNPQRSTVWXZ';
+
+const pending = new Map(); // device_code -> request
+
+function generateUserCode() {
+  const chars = Array.from(crypto.randomBytes(8), (b) => USER_CODE_ALPHABET[b % USER_CODE_ALPHABET.length]);
+  return `${chars.slice(0, 4).join('')}-${chars.slice(4).join('')}`;
+}
+
+export function normalizeUserCode(code) {
+  const clean = String(code || '').toUpperCase().replace(/[^A-Z]/g, '');
+  return clean.length === 8 ? `${clean.slice(0, 4)}-${clean.slice(4)}` : clean;
+}
+
+function purgeExpired() {
+  const now = Date.now();
+  for (const [deviceCode, request] of pending) {
+    if (request.expiresAt < now) pending.delete(deviceCode);
+  }
+}
+
+export function createDeviceRequest(device) {
+  purgeExpired();
+  const request = {
+    deviceCode: crypto.randomBytes(32).toString('hex'),
+    userCode: generateUserCode(),
+    device,
+    userId: null,
+    denied: false,
+    expiresAt: Date.now() + CODE_TTL_MS,
+    lastPoll: 0,
+  };
+  pending.set(request.deviceCode, request);
+  return { ...request, expiresIn: CODE_TTL_MS / 1000, interval: POLL_INTERVAL_S };
+}
+
+export function getRequestByUserCode(userCode) {
+  purgeExpired();
+  const code = normalizeUserCode(userCode);
+  for (const request of pending.values()) {
+    if (request.userCode === code) return request;
+  }
+  return null;
+}
+
+export function approveRequest(request, userId) {
+  request.userId = userId;
+}
+
+export function denyRequest(request) {
+  request.denied = true;
+}
+
+/**
+ * Poll result for a device_code: { error } with an RFC 8628 error code, or
+ * { userId, device } once approved (the request is consumed).
+ */
+export function pollDeviceRequest(deviceCode) {
+  const request = pending.get(deviceCode);
+  if (!request || request.expiresAt < Date.now()) {
+    pending.delete(deviceCode);
+    return { error: 'expired_token' };
+  }
+  if (request.denied) {
+    pending.delete(deviceCode);
+    return { error: 'access_denied' };
+  }
+  const now = Date.now();
+  // A second of slack for timer jitter on the client
+  const tooSoon = now - request.lastPoll < (POLL_INTERVAL_S - 1) * 1000;
+  request.lastPoll = now;
+  if (request.userId === null) {
+    return { error: tooSoon ? 'slow_down' : 'authorization_pending' };
+  }
+  pending.delete(deviceCode);
+  return { userId: request.userId, device: request.device };
+}
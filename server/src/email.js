@@ -57,3 +57,32 @@ export async function sendVerificationCode(email, code) {
   console.log(`[Email] Verification code sent to ${email}`);
   return true;
 }
+
+export async function sendPasswordResetCode(email, code) {
+  if (!transporter) {
+    console.warn('[Email] Cannot send — SMTP not configured');
+    return false;
+  }
+
+  const from = process.env.SMTP_FROM || process.env.SMTP_USER;
+
+  await transporter.sendMail({
+    from,
+    to: email,
+    subject: 'Remote Clauding — Password Reset',
+    text: `Your password reset code is: ${code}\n\nThis code expires in 15 minutes. If you did not request a reset, ignore this email.`,
+    html: `
+      <div style="font-family: sans-serif; max-width: 400px; margin: 0 auto; padding: 20px;">
+        <h2 style="color: #d97757;">Remote Clauding</h2>
+        <p>Your password reset code is:</p>
+        <div style="font-size: 32px; font-weight: bold; letter-spacing: 8px; text-align: center; padding: 20px; background: #f5f5f5; border-radius: 8px; margin: 16px 0;">
+          ${code}
+        </div>
+        <p style="color: #666; font-size: 14px;">This code expires in 15 minutes. If you did not request a reset, ignore this email.</p>
+      </div>
+    `,
+  });
+
+  console.log(`[Email] Password reset code sent to ${email}`);
+  return true;
+}
@@ -13,12 +13,22 @@ import {
   initDb, createUser, getUserByEmail, getUserByToken, getUserById,
   getAllUsers, updateUserStatus,
   setVerificationCode, verifyEmail,
+  setResetCode, recordResetFailure, resetPassword,
+  upsertDevice, getDevicesByUserId, renameDevice, deleteDevice,
 } from './db.js';
-import { authMiddleware, validateToken, REQUIRE_EMAIL_VERIFICATION, REQUIRE_MODERATION } from './auth.js';
+import {
+  authMiddleware, validateToken, loginBlockReason, REQUIRE_EMAIL_VERIFICATION, REQUIRE_MODERATION,
+} from './auth.js';
 import { getAllSessions, getSessionByToken } from './sessions.js';
 import { initPush, addSubscription, removeSubscription, getVapidPublicKey } from './push.js';
-import { initEmail, sendVerificationCode } from './email.js';
-import { setupWebSocket } from './relay.js';
+import { initEmail, sendVerificationCode, sendPasswordResetCode } from './email.js';
+import { setupWebSocket, disconnectDevice } from './relay.js';
+import {
+  createDeviceRequest, getRequestByUserCode, approveRequest, denyRequest, pollDeviceRequest,
+  normalizeUserCode,
+} from './device-auth.js';
+import { createPairingCode, redeemPairingCode } from './pairing.js';
+import { createRateLimiter } from './rate-limit.js';
 
 const __dirname = path.dirname(fileURLToPath(import.meta.url));
 const app = express();
@@ -26,6 +36,11 @@ const server = createServer(app);
 
 app.use(express.json());
 
+if (process.env.TRUST_PROXY) {
+  const hops = Number(process.env.TRUST_PROXY);
+  app.set('trust proxy', Number.isInteger(hops) ? hops : process.env.TRUST_PROXY);
+}
+
 // Serve PWA static files
 const webDistPath = path.join(__dirname, '..', '..', 'web', 'dist');
 app.use(express.static(webDistPath));
@@ -41,6 +56,24 @@ function generateVerificationCode() {
   return String(Math.floor(100000 + Math.random() * 900000));
 }
 
+const MAX_DEVICE_FIELD = 64;
+
+function cleanDeviceName(name) {
+  const trimmed = typeof name === 'string' ? name.trim().slice(0, MAX_DEVICE_FIELD) : '';
+  return trimmed || 'Unnamed device';
+}
+
+// Clients that send a device_id get their own token, which can be revoked
+// without signing out the account's other machines. Others get the account token.
+function tokenForDevice(userId, accountToken, { device_id, device_name } = {}) {
+  if (typeof device_id !== 'string' || !device_id || device_id.length > MAX_DEVICE_FIELD) {
+    return accountToken;
+  }
+  const token = uuidv4();
+  upsertDevice(userId, device_id, cleanDeviceName(device_name), token);
+  return token;
+}
+
 async function sendCodeToUser(userId, email) {
   const code = generateVerificationCode();
   const expires = new Date(Date.now() + 15 * 60 * 1000).toISOString();
@@ -84,7 +117,7 @@ app.post('/api/auth/register', async (req, res) => {
       ? `Registration successful. Please ${steps.join(' and ')}.`
       : 'Registration successful.';
 
-    const response = { message, auth_token: user.auth_token };
+    const response = { message, auth_token: tokenForDevice(user.id, user.auth_token, req.body) };
     res.json(response);
   } catch (err) {
     console.error('[Auth] Registration error:', err.message);
@@ -117,7 +150,7 @@ app.post('/api/auth/verify-email', (req, res) => {
   const message = (!REQUIRE_MODERATION || user.status === 'approved')
     ? 'Email verified.'
     : 'Email verified. Pending admin approval.';
-  res.json({ message, auth_token: user.auth_token });
+  res.json({ message, auth_token: tokenForDevice(user.id, user.auth_token, req.body) });
 });
 
 app.post('/api/auth/resend-code', async (req, res) => {
@@ -159,22 +192,238 @@ app.post('/api/auth/login', async (req, res) => {
     if (!match) {
       return res.status(401).json({ error: 'Invalid credentials' });
     }
-    if (REQUIRE_EMAIL_VERIFICATION && !user.email_verified) {
-      return res.status(403).json({ error: 'Email not verified' });
+    const blocked = loginBlockReason(user);
+    if (blocked) {
+      return res.status(403).json({ error: blocked });
     }
-    if (REQUIRE_MODERATION && user.status === 'pending') {
-      return res.status(403).json({ error: 'Account pending admin approval' });
-    }
-    if (user.status === 'rejected') {
-      return res.status(403).json({ error: 'Account has been rejected' });
-    }
-    res.json({ auth_token: user.auth_token });
+    res.json({ auth_token: tokenForDevice(user.id, user.auth_token, req.body) });
   } catch (err) {
     console.error('[Auth] Login error:', err.message);
     res.status(500).json({ error: 'Login failed' });
   }
 });
 
+// --- Device authorization (browser sign-in for the desktop app) ---
+
+function publicUrl(req) {
+  return process.env.PUBLIC_URL?.replace(/\/$/, '') || `${req.protocol}://${req.get('host')}`;
+}
+
+app.post('/api/auth/device-code', (req, res) => {
+  const { device_id, device_name } = req.body || {};
+  const request = createDeviceRequest({ device_id, device_name });
+  const verificationUri = `${publicUrl(req)}/device`;
+  res.json({
+    device_code: request.deviceCode,
+    user_code: request.userCode,
+    verification_uri: verificationUri,
+    verification_uri_complete: `${verificationUri}?code=${encodeURIComponent(request.userCode)}`,
+    expires_in: request.expiresIn,
+    interval: request.interval,
+  });
+});
+
+app.post('/api/auth/device-token', (req, res) => {
+  const { device_code } = req.body || {};
+  if (!device_code) {
+    return res.status(400).json({ error: 'device_code is required' });
+  }
+  const result = pollDeviceRequest(device_code);
+  if (result.error) {
+    return res.status(400).json({ error: result.error });
+  }
+  const user = getUserById(result.userId);
+  if (!user) {
+    return res.status(400).json({ error: 'access_denied' });
+  }
+  res.json({ auth_token: tokenForDevice(user.id, user.auth_token, result.device) });
+});
+
+function escapeHtml(value) {
+  return String(value ?? '').replace(/[&<>"']/g, (c) => (
+    { '&': '&amp;', '<': '&lt;', '>': '&gt;', '"': '&quot;', "'": '&#39;' }[c]
+  ));
+}
+
+function renderDevicePage({ code = '', email = '', error = '', done = '' } = {}) {
+  const body = done
+    ? `<p class="done">${escapeHtml(done)}</p>`
+    : `${error ? `<p class="error">${escapeHtml(error)}</p>` : ''}
+      <form method="post" action="/device">
+        <label>Code shown in the app<input name="code" value="${escapeHtml(code)}" autocomplete="off" required></label>
+        <label>Email<input name="email" type="email" value="${escapeHtml(email)}" required></label>
+        <label>Password<input name="password" type="password" required></label>
+        <button name="action" value="approve">Sign in this device</button>
+        <button name="action" value="deny" class="secondary" formnovalidate>Deny</button>
+      </form>`;
+  return `<!doctype html>
+<html><head><meta charset="utf-8"><meta name="viewport" content="width=device-width, initial-scale=1">
+<title>Remote Clauding — Device sign-in</title>
+<style>
+  body { font-family: system-ui, sans-serif; max-width: 360px; margin: 48px auto; padding: 0 16px; }
+  label { display: block; margin-bottom: 12px; font-size: 14px; }
+  input { display: block; width: 100%; box-sizing: border-box; padding: 8px; margin-top: 4px; }
+  button { padding: 8px 16px; margin-right: 8px; }
+  .error { color: #c0392b; } .done { color: #27ae60; }
+</style></head>
+<body><h2>Device sign-in</h2>${body}</body></html>`;
+}
+
+app.get('/device', (req, res) => {
+  res.send(renderDevicePage({ code: normalizeUserCode(req.query.code) }));
+});
+
+// Password checks per email and per client IP, so the form can't be used to guess passwords
+const deviceLoginByEmail = createRateLimiter({ windowMs: 15 * 60 * 1000, max: 10 });
+const deviceLoginByIp = createRateLimiter({ windowMs: 15 * 60 * 1000, max: 30 });
+
+app.post('/device', express.urlencoded({ extended: false }), async (req, res) => {
+  const { code, email, password, action } = req.body;
+  const request = getRequestByUserCode(code);
+  if (!request) {
+    return res.status(400).send(renderDevicePage({ code, email, error: 'Unknown or expired code' }));
+  }
+  if (action === 'deny') {
+    denyRequest(request);
+    return res.send(renderDevicePage({ done: 'Sign-in denied. You can close this tab.' }));
+  }
+
+  const emailAllowed = deviceLoginByEmail.hit(String(email || '').toLowerCase());
+  const ipAllowed = deviceLoginByIp.hit(req.ip);
+  if (!emailAllowed || !ipAllowed) {
+    return res.status(429).send(renderDevicePage({ code, email, error: 'Too many attempts, try again later' }));
+  }
+
+  const user = email ? getUserByEmail(email) : null;
+  const match = user && password ? await bcrypt.compare(password, user.password_hash) : false;
+  if (!match) {
+    return res.status(401).send(renderDevicePage({ code, email, error: 'Invalid credentials' }));
+  }
+  const blocked = loginBlockReason(user);
+  if (blocked) {
+    return res.status(403).send(renderDevicePage({ code, email, error: blocked }));
+  }
+
+  approveRequest(request, user.id);
+  res.send(renderDevicePage({ done: 'Device signed in. You can return to the app.' }));
+});
+
+// --- Phone pairing (QR code shown by the desktop app) ---
+
+app.post('/api/auth/pairing-code', authMiddleware, (req, res) => {
+  if (req.user.isSuperUser) {
+    return res.status(400).json({ error: 'Pairing codes need a user account' });
+  }
+  const { code, expiresAt } = createPairingCode(req.user.id);
+  res.json({ code, expires_at: expiresAt });
+});
+
+app.post('/api/auth/pairing-code/redeem', (req, res) => {
+  const { code } = req.body || {};
+  if (!code) {
+    return res.status(400).json({ error: 'code is required' });
+  }
+  const userId = redeemPairingCode(code);
+  const user = userId === null ? null : getUserById(userId);
+  if (!user) {
+    return res.status(400).json({ error: 'Invalid or expired pairing code' });
+  }
+  const blocked = loginBlockReason(user);
+  if (blocked) {
+    return res.status(403).json({ error: blocked });
+  }
+  res.json({ auth_token: tokenForDevice(user.id, user.auth_token, req.body) });
+});
+
+// --- Password reset ---
+
+// Wrong codes allowed before a reset code is invalidated
+const MAX_RESET_ATTEMPTS = 5;
+// Confirm attempts per email and per client IP, so six-digit codes can't be guessed
+const resetConfirmByEmail = createRateLimiter({ windowMs: 15 * 60 * 1000, max: 10 });
+const resetConfirmByIp = createRateLimiter({ windowMs: 15 * 60 * 1000, max: 30 });
+// Reset emails per address and per client IP, so no inbox can be flooded
+const resetRequestByEmail = createRateLimiter({ windowMs: 15 * 60 * 1000, max: 3 });
+const resetRequestByIp = createRateLimiter({ windowMs: 15 * 60 * 1000, max: 20 });
+
+app.post('/api/auth/password-reset/request', async (req, res) => {
+  const { email } = req.body;
+  if (!email) {
+    return res.status(400).json({ error: 'Email is required' });
+  }
+
+  const emailAllowed = resetRequestByEmail.hit(String(email).toLowerCase());
+  const ipAllowed = resetRequestByIp.hit(req.ip);
+  if (!emailAllowed || !ipAllowed) {
+    return res.status(429).json({ error: 'Too many attempts, try again later' });
+  }
+
+  // Same answer whether or not the account exists, so emails can't be probed
+  const message = 'If the account exists, a reset code has been sent';
+  const user = getUserByEmail(email);
+  if (!user) {
+    return res.json({ message });
+  }
+
+  try {
+    const code = generateVerificationCode();
+    const expires = new Date(Date.now() + 15 * 60 * 1000).toISOString();
+    setResetCode(user.id, code, expires);
+    await sendPasswordResetCode(email, code);
+    res.json({ message });
+  } catch (err) {
+    console.error('[Auth] Password reset request error:', err.message);
+    res.status(500).json({ error: 'Failed to send reset code' });
+  }
+});
+
+app.post('/api/auth/password-reset/confirm', async (req, res) => {
+  const { email, code, password } = req.body;
+  if (!email || !code || !password) {
+    return res.status(400).json({ error: 'Email, code and password are required' });
+  }
+  if (password.length < 6) {
+    return res.status(400).json({ error: 'Password must be at least 6 characters' });
+  }
+
+  const emailAllowed = resetConfirmByEmail.hit(String(email).toLowerCase());
+  const ipAllowed = resetConfirmByIp.hit(req.ip);
+  if (!emailAllowed || !ipAllowed) {
+    return res.status(429).json({ error: 'Too many attempts, try again later' });
+  }
+
+  const user = getUserByEmail(email);
+  if (!user || !user.reset_code) {
+    return res.status(400).json({ error: 'Invalid reset code' });
+  }
+  if (user.reset_code !== code) {
+    const attempts = recordResetFailure(user.id, MAX_RESET_ATTEMPTS);
+    return res.status(400).json({
+      error: attempts >= MAX_RESET_ATTEMPTS
+        ? 'Too many wrong codes, request a new one'
+        : 'Invalid reset code',
+    });
+  }
+  if (new Date(user.reset_code_expires) < new Date()) {
+    return res.status(400).json({ error: 'Reset code expired' });
+  }
+  const blocked = loginBlockReason(user);
+  if (blocked) {
+    return res.status(403).json({ error: blocked });
+  }
+
+  try {
+    // New token signs out every device that used the old password
+    const passwordHash = await bcrypt.hash(password, 10);
+    const authToken = uuidv4();
+    resetPassword(user.id, passwordHash, authToken);
+    res.json({ message: 'Password updated.', auth_token: tokenForDevice(user.id, authToken, req.body) });
+  } catch (err) {
+    console.error('[Auth] Password reset error:', err.message);
+    res.status(500).json({ error: 'Password reset failed' });
+  }
+});
+
 // --- Account status (token lookup without approval check) ---
 
 app.get('/api/auth/me', (req, res) => {
@@ -190,9 +439,41 @@ app.get('/api/auth/me', (req, res) => {
     email: user.email,
     status: user.status,
     email_verified: !!user.email_verified,
+    device_id: user.device_id || null,
   });
 });
 
+// --- Device routes ---
+
+app.get('/api/devices', authMiddleware, (req, res) => {
+  const devices = getDevicesByUserId(req.user.id).map((d) => ({
+    ...d,
+    current: d.device_id === req.user.device_id,
+  }));
+  res.json({ devices });
+});
+
+app.patch('/api/devices/:deviceId', authMiddleware, (req, res) => {
+  if (typeof req.body.name !== 'string' || !req.body.name.trim()) {
+    return res.status(400).json({ error: 'Name is required' });
+  }
+  if (!renameDevice(req.user.id, req.params.deviceId, cleanDeviceName(req.body.name))) {
+    return res.status(404).json({ error: 'Device not found' });
+  }
+  res.json({ success: true });
+});
+
+app.delete('/api/devices/:deviceId', authMiddleware, (req, res) => {
+  if (req.params.deviceId === req.user.device_id) {
+    return res.status(400).json({ error: 'Cannot revoke the current device; log out instead' });
+  }
+  if (!deleteDevice(req.user.id, req.params.deviceId)) {
+    return res.status(404).json({ error: 'Device not found' });
+  }
+  disconnectDevice(req.user.id, req.params.deviceId);
+  res.json({ success: true });
+});
+
 // --- Push routes ---
 
 // Get VAPID public key for push subscription
@@ -0,0 +1,32 @@
+import crypto from 'crypto';
+
+// One-time codes that sign a phone into the account of the desktop that
+// showed the QR code. The code is the only secret in the QR, so it is short-lived.
+
+const CODE_TTL_MS = 5 * 60 * 1000;
+
+const codes = new Map(); // code -> { userId, expiresAt }
+
+function purgeExpired() {
+  const now = Date.now();
+  for (const [code, entry] of codes) {
+    if (entry.expiresAt < now) codes.delete(code);
+  }
+}
+
+export function createPairingCode(userId) {
+  purgeExpired();
+  const code = crypto.randomBytes(24).toString('base64url');
+  const expiresAt = Date.now() + CODE_TTL_MS;
+  codes.set(code, { userId, expiresAt });
+  return { code, expiresAt: new Date(expiresAt).toISOString() };
+}
+
+/** The user a code was issued to, or null. A code works once. */
+export function redeemPairingCode(code) {
+  const key = String(code || '');
+  const entry = codes.get(key);
+  if (!entry) return null;
+  codes.delete(key);
+  return entry.expiresAt < Date.now() ? null : entry.userId;
+}
@@ -0,0 +1,19 @@
+// Fixed-window request counters, kept in memory per key (e.g. an email or IP)
+
+export function createRateLimiter({ windowMs, max }) {
+  const windows = new Map(); // key -> { count, resetAt }
+
+  return {
+    /** Counts a hit for `key`; false once the key is over the limit. */
+    hit(key) {
+      const now = Date.now();
+      for (const [k, w] of windows) {
+        if (w.resetAt <= now) windows.delete(k);
+      }
+      const window = windows.get(key) || { count: 0, resetAt: now + windowMs };
+      window.count += 1;
+      windows.set(key, window);
+      return window.count <= max;
+    },
+  };
+}
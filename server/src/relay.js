@@ -12,6 +12,7 @@ import {
   updateSessionStatus,
 } from './sessions.js';
 import { sendNotification } from './push.js';
+import { touchDevice } from './db.js';
 
 // Ask connected clients if they're active before sending a push notification.
 // Returns true if any client responds as active within the timeout.
@@ -72,10 +73,16 @@ export function setupWebSocket(server) {
         socket.destroy();
         return;
       }
+      // Device tokens identify the machine; older agents may still pass it along
+      const deviceId = user.device_id || url.searchParams.get('device_id') || null;
+      const deviceName = user.device_name || url.searchParams.get('device_name') || null;
+      if (user.device_id) touchDevice(user.id, user.device_id);
       wss.handleUpgrade(request, socket, head, (ws) => {
         ws._type = 'agent';
         ws._sessionId = null;
         ws._userId = user.id;
+        ws._deviceId = deviceId;
+        ws._deviceName = deviceName;
         wss.emit('connection', ws, request);
       });
     } else if (pathname === '/ws/client') {
@@ -148,7 +155,19 @@ function canAccessSession(ws, sessionId) {
   return target && target.userId === ws._userId;
 }
 
+// Agent connections, so revoking a device can drop its socket
+const agentConnections = new Set();
+
+export function disconnectDevice(userId, deviceId) {
+  for (const ws of agentConnections) {
+    if (ws._userId === userId && ws._deviceId === deviceId) {
+      ws.close(4001, 'Device revoked');
+    }
+  }
+}
+
 function handleAgentConnection(ws) {
+  agentConnections.add(ws);
   ws._alive = true;
   ws.on('pong', () => { ws._alive = true; });
 
@@ -167,6 +186,7 @@ function handleAgentConnection(ws) {
           projectPath: msg.projectPath,
           sessionToken: msg.sessionToken,
           userId: ws._userId,
+          deviceName: ws._deviceName,
           autoAccept: msg.autoAccept ?? false,
         });
         session.agentWs = ws;
@@ -293,6 +313,7 @@ function handleAgentConnection(ws) {
   });
 
   ws.on('close', () => {
+    agentConnections.delete(ws);
     console.log(`[WS] Agent disconnected (session: ${ws._sessionId})`);
     if (ws._sessionId) {
       // Auto-remove session when agent disconnects
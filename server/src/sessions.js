@@ -9,6 +9,7 @@ export function createSession(sessionId, info) {
     projectPath: info.projectPath,
     sessionToken: info.sessionToken || null,
     userId: info.userId || 0,
+    deviceName: info.deviceName || null,
     autoAccept: info.autoAccept ?? false,
     status: 'idle',
     messages: existing ? existing.messages : [],
@@ -61,6 +62,7 @@ export function summarizeSession(session) {
   return {
     id: session.id,
     projectName: session.projectName,
+    deviceName: session.deviceName,
     status: session.status,
     autoAccept: session.autoAccept,
     messageCount: countVisibleMessages(session.messages),
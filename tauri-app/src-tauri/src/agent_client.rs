@@ -0,0 +1,184 @@
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+// ── Client for the agent's local HTTP API (lib/http-server.js) ──
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct AgentHealth {
+    #[serde(default)]
+    pub relay_public_url: String,
+    #[serde(default)]
+    pub version: String,
+    #[serde(default)]
+    pub relay_connected: bool,
+    #[serde(default)]
+    pub uptime: Option<u64>,
+    #[serde(default)]
+    pub last_error: Option<String>,
+    /// The relay answered 401 to the agent's token
+    #[serde(default)]
+    pub auth_rejected: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(rename_all(deserialize = "camelCase"))]
+pub struct AgentSession {
+    pub id: String,
+    pub project_name: String,
+    pub project_path: String,
+    #[serde(default)]
+    pub session_token: String,
+    #[serde(default)]
+    pub status: String,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all(deserialize = "camelCase"))]
+pub struct ShareResult {
+    pub session: AgentSession,
+    #[serde(default)]
+    pub already_shared: bool,
+    #[serde(default)]
+    pub relay_public_url: String,
+}
+
+/// Messages the agent accepts from local clients on its `/ws` socket, the
+/// same ones the relay forwards from the phone.
+#[derive(Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AgentCommand {
+    #[serde(rename_all = "camelCase")]
+    UserMessage { session_id: String, content: String },
+    #[serde(rename_all = "camelCase")]
+    StopMessage { session_id: String },
+    #[serde(rename_all = "camelCase")]
+    PermissionResponse {
+        session_id: String,
+        permission_id: String,
+        action: String,
+    },
+    #[serde(rename_all = "camelCase")]
+    DismissQuestion { session_id: String },
+    #[serde(rename_all = "camelCase")]
+    SetAutoAccept { session_id: String, auto_accept: bool },
+}
+
+#[derive(Deserialize)]
+struct SessionsBody {
+    #[serde(default)]
+    sessions: Vec<AgentSession>,
+}
+
+pub struct AgentClient {
+    base_url: String,
+    client: reqwest::blocking::Client,
+}
+
+impl AgentClient {
+    pub fn new(port: u16) -> Self {
+        Self::with_base_url(format!("http://127.0.0.1:{}", port))
+    }
+
+    pub fn with_base_url(base_url: String) -> Self {
+        // Loopback only, so any configured or environment proxy is skipped
+        let client = reqwest::blocking::Client::builder()
+            .timeout(Duration::from_secs(5))
+            .connect_timeout(Duration::from_secs(2))
+            .no_proxy()
+            .build()
+            .unwrap_or_else(|_| reqwest::blocking::Client::new());
+
+        AgentClient { base_url, client }
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base_url, path)
+    }
+
+    fn session_url(&self, session_id: &str, action: &str) -> Result<String, String> {
+        // Session IDs are UUIDs; refuse anything that would change the path
+        if session_id.is_empty() || !session_id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+            return Err(format!("Invalid session id: {}", session_id));
+        }
+        Ok(self.url(&format!("/sessions/{}{}", session_id, action)))
+    }
+
+    /// Sends the request and maps non-2xx responses to the agent's `error` field.
+    fn send(&self, request: reqwest::blocking::RequestBuilder) -> Result<reqwest::blocking::Response, String> {
+        let res = request
+            .send()
+            .map_err(|e| format!("Agent not reachable: {}", e))?;
+
+        let status = res.status();
+        if !status.is_success() {
+            let body: serde_json::Value = res.json().unwrap_or_default();
+            let error = body["error"]
+                .as_str()
+                .map(|e| e.to_string())
+                .unwrap_or_else(|| format!("Agent returned {}", status));
+            return Err(error);
+        }
+
+        Ok(res)
+    }
+
+    pub fn health(&self) -> Result<AgentHealth, String> {
+        let res = self.send(self.client.get(self.url("/health")))?;
+        Ok(res.json().unwrap_or_default())
+    }
+
+    pub fn is_healthy(&self) -> bool {
+        self.health().is_ok()
+    }
+
+    pub fn list_sessions(&self) -> Result<Vec<AgentSession>, String> {
+        let res = self.send(self.client.get(self.url("/sessions")))?;
+        let body: SessionsBody = res.json().map_err(|e| format!("Parse error: {}", e))?;
+        Ok(body.sessions)
+    }
+
+    pub fn share_session(&self, project_path: &str, project_name: Option<&str>) -> Result<ShareResult, String> {
+        let mut body = serde_json::json!({ "projectPath": project_path });
+        if let Some(name) = project_name {
+            body["projectName"] = serde_json::Value::from(name);
+        }
+
+        let res = self.send(self.client.post(self.url("/sessions/share")).json(&body))?;
+        res.json().map_err(|e| format!("Parse error: {}", e))
+    }
+
+    pub fn reshare_session(&self, session_id: &str) -> Result<(), String> {
+        self.send(self.client.post(self.session_url(session_id, "/reshare")?))?;
+        Ok(())
+    }
+
+    pub fn unshare_session(&self, session_id: &str) -> Result<(), String> {
+        self.send(self.client.post(self.session_url(session_id, "/unshare")?))?;
+        Ok(())
+    }
+
+    pub fn remove_session(&self, session_id: &str) -> Result<(), String> {
+        self.send(self.client.delete(self.session_url(session_id, "")?))?;
+        Ok(())
+    }
+
+    /// Sends one command over a short-lived connection to the agent's `/ws`.
+    pub fn send_command(&self, command: &AgentCommand) -> Result<(), String> {
+        let ws_url = format!("{}/ws", self.base_url.replacen("http://", "ws://", 1));
+        let (mut socket, _) = tungstenite::connect(ws_url.as_str())
+            .map_err(|e| format!("Agent not reachable: {}", e))?;
+        if let tungstenite::stream::MaybeTlsStream::Plain(stream) = socket.get_ref() {
+            let _ = stream.set_read_timeout(Some(Duration::from_secs(2)));
+        }
+
+        let payload = serde_json::to_string(command).map_err(|e| format!("Encode error: {}", e))?;
+        socket
+            .send(tungstenite::Message::Text(payload))
+            .map_err(|e| format!("Cannot send to agent: {}", e))?;
+        let _ = socket.close(None);
+        // Drain until the close handshake completes so the message is flushed
+        while socket.read().is_ok() {}
+        Ok(())
+    }
+}
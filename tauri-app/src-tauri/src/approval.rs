@@ -0,0 +1,92 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{Emitter, Manager};
+use tauri_plugin_notification::NotificationExt;
+
+use crate::relay_client::{RelayClient, RelayError};
+
+// ── Background polling of a pending account's approval status ──
+
+const INITIAL_INTERVAL_SECS: u64 = 15;
+const MAX_INTERVAL_SECS: u64 = 300;
+
+/// Polls `/api/auth/me` while the account is pending, backing off from 15s
+/// to 5 min. When an admin approves or rejects it, emits
+/// `account_status_changed`, shows a notification and, once approved,
+/// starts the agent.
+#[derive(Default)]
+pub struct ApprovalWatcher {
+    running: AtomicBool,
+}
+
+impl ApprovalWatcher {
+    /// Starts polling unless a poller is already running. Returns false in
+    /// that case.
+    pub fn start(self: &Arc<Self>, app: tauri::AppHandle) -> bool {
+        if self.running.swap(true, Ordering::SeqCst) {
+            return false;
+        }
+        let watcher = self.clone();
+        tauri::async_runtime::spawn(async move {
+            watcher.watch(&app).await;
+            watcher.running.store(false, Ordering::SeqCst);
+        });
+        true
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running.load(Ordering::SeqCst)
+    }
+
+    async fn watch(&self, app: &tauri::AppHandle) {
+        let mut interval = INITIAL_INTERVAL_SECS;
+        let mut was_pending = false;
+
+        loop {
+            let relay = app.state::<RelayClient>().inner().clone();
+            let result = match relay.authenticated() {
+                Ok(client) => client.me().await,
+                Err(e) => Err(e),
+            };
+
+            match crate::check_session(app, result).await {
+                Ok(account) if account.status == "pending" => was_pending = true,
+                Ok(account) => {
+                    // Only a transition is news; an already approved account
+                    // needs nothing from us
+                    if was_pending {
+                        on_decided(app, &account.status).await;
+                    }
+                    return;
+                }
+                Err(RelayError::NotLoggedIn) | Err(RelayError::SessionExpired) => return,
+                Err(e) => log::warn!("Account status check failed: {}", e),
+            }
+
+            tokio::time::sleep(Duration::from_secs(interval)).await;
+            interval = (interval * 2).min(MAX_INTERVAL_SECS);
+        }
+    }
+}
+
+async fn on_decided(app: &tauri::AppHandle, status: &str) {
+    log::info!("Account status changed to {}", status);
+    let _ = app.emit("account_status_changed", serde_json::json!({ "status": status }));
+
+    let (title, body) = match status {
+        "approved" => ("Account approved", "You can now use Remote Clauding from your phone"),
+        "rejected" => ("Account rejected", "An administrator rejected your account"),
+        _ => ("Account status changed", status),
+    };
+    if let Err(e) = app.notification().builder().title(title).body(body).show() {
+        log::warn!("Cannot show notification: {}", e);
+    }
+
+    if status == "approved" {
+        let app = app.clone();
+        if let Err(e) = crate::blocking(move || crate::start_agent_blocking(app, None)).await {
+            log::warn!("Agent start after approval failed: {}", e);
+        }
+    }
+}
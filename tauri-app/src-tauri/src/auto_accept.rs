@@ -0,0 +1,477 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::agent_client::{AgentClient, AgentCommand, AgentSession};
+use crate::session_events::AgentEvent;
+
+// ── Per-project auto-accept policies ──
+
+const AUDIT_FILE: &str = "auto-accept-audit.jsonl";
+/// Past this size the audit log is rotated to `AUDIT_FILE.1`
+const AUDIT_MAX_BYTES: u64 = 1024 * 1024;
+
+/// Auto-accept rule for one project. With no tools and no globs the agent's
+/// own auto-accept is switched on; otherwise the desktop app answers only
+/// the permission requests the rule covers and leaves the rest to the user.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct AutoAcceptPolicy {
+    pub project_path: String,
+    #[serde(default)]
+    pub allowed_tools: Vec<String>,
+    /// Absolute, or relative to the project path
+    #[serde(default)]
+    pub path_globs: Vec<String>,
+    #[serde(default)]
+    pub max_duration_mins: Option<u64>,
+}
+
+/// `path` with `.` components dropped. None if it has a `..` component:
+/// without touching the filesystem there is no telling where it ends up, so
+/// it never matches a rule.
+fn clean(path: &Path) -> Option<PathBuf> {
+    let mut cleaned = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::ParentDir => return None,
+            Component::CurDir => {}
+            other => cleaned.push(other),
+        }
+    }
+    Some(cleaned)
+}
+
+/// `path` made absolute against the project, then `clean`ed.
+fn resolve(project_path: &str, path: &str) -> Option<PathBuf> {
+    clean(&Path::new(project_path).join(path))
+}
+
+/// `path` with symlinks resolved as far as it exists, so a link inside the
+/// project can't stand in for a file outside it.
+fn canonicalize_existing(path: &Path) -> PathBuf {
+    let mut existing = path;
+    let mut missing = Vec::new();
+    loop {
+        if let Ok(canonical) = existing.canonicalize() {
+            return missing.iter().rev().fold(canonical, |full, name| full.join(name));
+        }
+        match (existing.parent(), existing.file_name()) {
+            (Some(parent), Some(name)) => {
+                missing.push(name);
+                existing = parent;
+            }
+            _ => return path.to_path_buf(),
+        }
+    }
+}
+
+impl AutoAcceptPolicy {
+    pub fn is_unrestricted(&self) -> bool {
+        self.allowed_tools.is_empty() && self.path_globs.is_empty()
+    }
+
+    /// Relative globs are rooted at the project with its symlinks resolved,
+    /// like the paths they are matched against; the project part is literal.
+    fn patterns(&self) -> Vec<glob::Pattern> {
+        let project = clean(Path::new(&self.project_path)).unwrap_or_default();
+        let root = glob::Pattern::escape(&canonicalize_existing(&project).to_string_lossy());
+        self.path_globs
+            .iter()
+            .filter_map(|g| clean(Path::new(g)))
+            .map(|glob| if glob.is_absolute() { glob } else { Path::new(&root).join(glob) })
+            // `validate` rejected unparsable globs when the policy was saved
+            .filter_map(|full| glob::Pattern::new(&full.to_string_lossy()).ok())
+            .collect()
+    }
+
+    /// Whether a permission request for `tool` with `input` falls under this rule.
+    pub fn allows(&self, tool: &str, input: &serde_json::Value) -> bool {
+        if !self.allowed_tools.is_empty() && !self.allowed_tools.iter().any(|t| t == tool) {
+            return false;
+        }
+        if self.path_globs.is_empty() {
+            return true;
+        }
+
+        // Tools without a path argument (e.g. Bash) can't satisfy a path rule
+        let paths = tool_paths(input);
+        if paths.is_empty() {
+            return false;
+        }
+
+        let options = glob::MatchOptions {
+            require_literal_separator: true,
+            ..Default::default()
+        };
+        let patterns = self.patterns();
+        paths.iter().all(|p| {
+            resolve(&self.project_path, p).is_some_and(|full| {
+                let full = canonicalize_existing(&full);
+                patterns.iter().any(|pat| pat.matches_path_with(&full, options))
+            })
+        })
+    }
+}
+
+fn tool_paths(input: &serde_json::Value) -> Vec<String> {
+    ["file_path", "path", "notebook_path"]
+        .iter()
+        .filter_map(|key| input[*key].as_str().map(|s| s.to_string()))
+        .collect()
+}
+
+pub fn validate(policy: &AutoAcceptPolicy) -> Result<(), String> {
+    if policy.project_path.trim().is_empty() {
+        return Err("Project path is required".to_string());
+    }
+    for g in &policy.path_globs {
+        glob::Pattern::new(g).map_err(|e| format!("Invalid glob {}: {}", g, e))?;
+        if Path::new(g).components().any(|c| c == Component::ParentDir) {
+            return Err(format!("Glob must not contain '..': {}", g));
+        }
+    }
+    if policy.max_duration_mins == Some(0) {
+        return Err("Max duration must be at least one minute".to_string());
+    }
+    Ok(())
+}
+
+pub fn find_policy<'a>(policies: &'a [AutoAcceptPolicy], project_path: &str) -> Option<&'a AutoAcceptPolicy> {
+    policies.iter().find(|p| p.project_path == project_path)
+}
+
+// ── Audit log ──
+
+fn audit_path() -> PathBuf {
+    crate::get_config_dir().join(AUDIT_FILE)
+}
+
+fn audit(session: &AgentSession, action: &str, detail: serde_json::Value) {
+    crate::ensure_config_dir();
+    let path = audit_path();
+    if fs::metadata(&path).is_ok_and(|m| m.len() >= AUDIT_MAX_BYTES) {
+        let _ = fs::rename(&path, path.with_extension("jsonl.1"));
+    }
+    let entry = serde_json::json!({
+        "timestamp": crate::now_millis(),
+        "session_id": session.id,
+        "project_path": session.project_path,
+        "action": action,
+        "detail": detail,
+    });
+    if let Ok(mut file) = fs::OpenOptions::new().create(true).append(true).open(&path) {
+        let _ = writeln!(file, "{}", entry);
+    }
+}
+
+/// What the audit log keeps of a tool call: its paths, or for a command
+/// only the program, never file contents or full command lines.
+fn audit_summary(tool: &str, input: &serde_json::Value) -> serde_json::Value {
+    let mut summary = serde_json::json!({ "tool": tool });
+    let paths = tool_paths(input);
+    if !paths.is_empty() {
+        summary["paths"] = serde_json::json!(paths);
+    } else if let Some(program) = input["command"].as_str().and_then(|c| c.split_whitespace().next()) {
+        summary["program"] = serde_json::json!(program);
+    }
+    summary
+}
+
+/// Most recent audit entries, newest last.
+pub fn read_audit(limit: usize) -> Vec<serde_json::Value> {
+    let path = audit_path();
+    let mut data = fs::read_to_string(path.with_extension("jsonl.1")).unwrap_or_default();
+    data.push_str(&fs::read_to_string(&path).unwrap_or_default());
+    let entries: Vec<serde_json::Value> = data
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect();
+    let skip = entries.len().saturating_sub(limit);
+    entries.into_iter().skip(skip).collect()
+}
+
+// ── Enforcement ──
+
+struct ActivePolicy {
+    session: AgentSession,
+    policy: AutoAcceptPolicy,
+    started: Instant,
+    /// Tells a max-duration timer whether its policy is still the one in force
+    generation: u64,
+}
+
+impl ActivePolicy {
+    fn expired(&self) -> bool {
+        self.policy
+            .max_duration_mins
+            .map(|mins| self.started.elapsed() >= Duration::from_secs(mins.saturating_mul(60)))
+            .unwrap_or(false)
+    }
+}
+
+/// Applies policies to sessions as they appear and answers permission
+/// requests they cover.
+#[derive(Default)]
+pub struct PolicyEnforcer {
+    sessions: Arc<Mutex<HashMap<String, Option<ActivePolicy>>>>,
+    next_generation: AtomicU64,
+}
+
+impl PolicyEnforcer {
+    /// Looks up and applies the configured policy for `session`, replacing
+    /// any policy already active for it.
+    pub fn apply(&self, session: &AgentSession) -> Result<Option<AutoAcceptPolicy>, String> {
+        let policies = crate::read_config().auto_accept_policies;
+        let policy = find_policy(&policies, &session.project_path).cloned();
+
+        if let Some(policy) = &policy {
+            let client = AgentClient::new(crate::get_agent_port());
+            client.send_command(&AgentCommand::SetAutoAccept {
+                session_id: session.id.clone(),
+                auto_accept: policy.is_unrestricted(),
+            })?;
+            audit(session, "policy_applied", serde_json::to_value(policy).unwrap_or_default());
+        }
+
+        let generation = self.next_generation.fetch_add(1, Ordering::SeqCst);
+        if let Ok(mut sessions) = self.sessions.lock() {
+            sessions.insert(
+                session.id.clone(),
+                policy.clone().map(|policy| ActivePolicy {
+                    session: session.clone(),
+                    policy,
+                    started: Instant::now(),
+                    generation,
+                }),
+            );
+        }
+
+        if let Some(mins) = policy.as_ref().filter(|p| p.is_unrestricted()).and_then(|p| p.max_duration_mins) {
+            let sessions = self.sessions.clone();
+            let session = session.clone();
+            std::thread::spawn(move || {
+                std::thread::sleep(Duration::from_secs(mins.saturating_mul(60)));
+                // Leave alone a policy that was re-applied or replaced meanwhile
+                let current = sessions
+                    .lock()
+                    .map(|s| matches!(s.get(&session.id), Some(Some(active)) if active.generation == generation))
+                    .unwrap_or(false);
+                if !current {
+                    return;
+                }
+                let client = AgentClient::new(crate::get_agent_port());
+                if client
+                    .send_command(&AgentCommand::SetAutoAccept {
+                        session_id: session.id.clone(),
+                        auto_accept: false,
+                    })
+                    .is_ok()
+                {
+                    audit(&session, "policy_expired", serde_json::Value::Null);
+                }
+            });
+        }
+
+        Ok(policy)
+    }
+
+    pub fn handle(&self, _app: &tauri::AppHandle, event: &AgentEvent) {
+        match event {
+            AgentEvent::SessionsUpdated { sessions } => {
+                let new_sessions: Vec<&AgentSession> = match self.sessions.lock() {
+                    Ok(mut tracked) => {
+                        tracked.retain(|id, _| sessions.iter().any(|s| &s.id == id));
+                        sessions.iter().filter(|s| !tracked.contains_key(&s.id)).collect()
+                    }
+                    Err(_) => return,
+                };
+                for session in new_sessions {
+                    if let Err(e) = self.apply(session) {
+                        log::warn!("Cannot apply auto-accept policy to {}: {}", session.id, e);
+                    }
+                }
+            }
+            AgentEvent::ClaudeOutput { session_id, message } if message["type"] == "permission_request" => {
+                let (session, permission_id, tool) = {
+                    let Ok(tracked) = self.sessions.lock() else {
+                        return;
+                    };
+                    let Some(Some(active)) = tracked.get(session_id) else {
+                        return;
+                    };
+                    let tool = message["toolName"].as_str().unwrap_or_default();
+                    if active.expired() || !active.policy.allows(tool, &message["toolInput"]) {
+                        return;
+                    }
+                    let Some(permission_id) = message["permissionId"].as_str() else {
+                        return;
+                    };
+                    (active.session.clone(), permission_id.to_string(), tool.to_string())
+                };
+
+                let client = AgentClient::new(crate::get_agent_port());
+                match client.send_command(&AgentCommand::PermissionResponse {
+                    session_id: session.id.clone(),
+                    permission_id,
+                    action: "allow".to_string(),
+                }) {
+                    Ok(()) => audit(
+                        &session,
+                        "permission_allowed",
+                        audit_summary(&tool, &message["toolInput"]),
+                    ),
+                    Err(e) => log::warn!("Cannot auto-accept permission: {}", e),
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn policy(tools: &[&str], globs: &[&str]) -> AutoAcceptPolicy {
+        AutoAcceptPolicy {
+            project_path: "/home/u/proj".to_string(),
+            allowed_tools: tools.iter().map(|t| t.to_string()).collect(),
+            path_globs: globs.iter().map(|g| g.to_string()).collect(),
+            max_duration_mins: None,
+        }
+    }
+
+    #[test]
+    fn tool_list_restricts_tools() {
+        let policy = policy(&["Edit", "Write"], &[]);
+        assert!(policy.allows("Edit", &json!({ "file_path": "/anywhere" })));
+        assert!(!policy.allows("Bash", &json!({ "command": "ls" })));
+        assert!(!policy.is_unrestricted());
+    }
+
+    #[test]
+    fn relative_globs_are_rooted_at_the_project() {
+        let policy = policy(&[], &["src/**"]);
+        assert!(policy.allows("Edit", &json!({ "file_path": "/home/u/proj/src/main.rs" })));
+        assert!(policy.allows("Edit", &json!({ "file_path": "src/a/b.rs" })));
+        assert!(policy.allows("Edit", &json!({ "file_path": "./src/main.rs" })));
+        assert!(!policy.allows("Edit", &json!({ "file_path": "/home/u/proj/Cargo.toml" })));
+        assert!(!policy.allows("Edit", &json!({ "file_path": "/home/u/other/src/main.rs" })));
+    }
+
+    #[test]
+    fn single_star_does_not_cross_directories() {
+        let policy = policy(&[], &["src/*.rs"]);
+        assert!(policy.allows("Write", &json!({ "file_path": "src/lib.rs" })));
+        assert!(!policy.allows("Write", &json!({ "file_path": "src/bin/main.rs" })));
+    }
+
+    #[test]
+    fn absolute_globs_match_as_given() {
+        let policy = policy(&[], &["/tmp/scratch/**"]);
+        assert!(policy.allows("Write", &json!({ "file_path": "/tmp/scratch/notes.md" })));
+        assert!(!policy.allows("Write", &json!({ "file_path": "notes.md" })));
+    }
+
+    #[test]
+    fn parent_components_never_match() {
+        let anywhere = policy(&[], &["**"]);
+        assert!(!anywhere.allows("Write", &json!({ "file_path": "../../etc/passwd" })));
+        assert!(!anywhere.allows("Write", &json!({ "file_path": "src/../../../etc/passwd" })));
+        assert!(!anywhere.allows("Write", &json!({ "file_path": "/home/u/proj/../.ssh/id_rsa" })));
+
+        let src = policy(&[], &["src/**"]);
+        assert!(!src.allows("Write", &json!({ "file_path": "src/../Cargo.toml" })));
+    }
+
+    #[test]
+    fn every_path_argument_must_match() {
+        let policy = policy(&[], &["src/**"]);
+        assert!(!policy.allows("Grep", &json!({ "file_path": "src/a.rs", "path": "/etc" })));
+        assert!(policy.allows("Grep", &json!({ "path": "src/nested" })));
+    }
+
+    #[test]
+    fn path_rules_reject_tools_without_paths() {
+        let policy = policy(&["Bash"], &["src/**"]);
+        assert!(!policy.allows("Bash", &json!({ "command": "rm -rf /" })));
+    }
+
+    #[test]
+    fn validates_policies() {
+        assert!(validate(&policy(&[], &["src/**"])).is_ok());
+        assert!(validate(&policy(&[], &["../**"])).is_err());
+        assert!(validate(&policy(&[], &["src/[*.rs"])).is_err());
+
+        let mut no_project = policy(&[], &[]);
+        no_project.project_path = " ".to_string();
+        assert!(validate(&no_project).is_err());
+
+        let mut zero = policy(&[], &[]);
+        zero.max_duration_mins = Some(0);
+        assert!(validate(&zero).is_err());
+    }
+
+    #[test]
+    fn project_path_is_matched_literally() {
+        let mut policy = policy(&[], &["src/*.rs"]);
+        policy.project_path = "/home/u/[draft] proj*".to_string();
+        assert!(policy.allows("Write", &json!({ "file_path": "src/lib.rs" })));
+        assert!(policy.allows("Write", &json!({ "file_path": "/home/u/[draft] proj*/src/lib.rs" })));
+        assert!(!policy.allows("Write", &json!({ "file_path": "/home/u/d proj-x/src/lib.rs" })));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn symlinks_out_of_the_project_never_match() {
+        let root = std::env::temp_dir().join(format!("rc-auto-accept-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("proj/src")).unwrap();
+        fs::create_dir_all(root.join("outside")).unwrap();
+        std::os::unix::fs::symlink(root.join("outside"), root.join("proj/src/link")).unwrap();
+
+        let mut policy = policy(&[], &["src/**"]);
+        policy.project_path = root.join("proj").to_string_lossy().to_string();
+        assert!(policy.allows("Write", &json!({ "file_path": "src/new.rs" })));
+        assert!(!policy.allows("Write", &json!({ "file_path": "src/link/secret" })));
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn huge_durations_do_not_overflow() {
+        let active = ActivePolicy {
+            session: serde_json::from_value(json!({ "id": "s1", "projectName": "proj", "projectPath": "/home/u/proj" }))
+                .unwrap(),
+            policy: AutoAcceptPolicy {
+                max_duration_mins: Some(u64::MAX),
+                ..policy(&[], &[])
+            },
+            started: Instant::now(),
+            generation: 0,
+        };
+        assert!(!active.expired());
+    }
+
+    #[test]
+    fn audit_keeps_paths_and_programs_only() {
+        let edit = audit_summary("Write", &json!({ "file_path": "src/a.rs", "content": "API_KEY=secret" }));
+        assert_eq!(edit, json!({ "tool": "Write", "paths": ["src/a.rs"] }));
+
+        let bash = audit_summary("Bash", &json!({ "command": "curl -H 'Authorization: Bearer x' example.com" }));
+        assert_eq!(bash, json!({ "tool": "Bash", "program": "curl" }));
+    }
+
+    #[test]
+    fn finds_policy_by_exact_project_path() {
+        let policies = vec![policy(&["Edit"], &[])];
+        assert!(find_policy(&policies, "/home/u/proj").is_some());
+        assert!(find_policy(&policies, "/home/u/proj/sub").is_none());
+    }
+}
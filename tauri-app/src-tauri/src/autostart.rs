@@ -0,0 +1,320 @@
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+// ── Start the agent at login (Linux: XDG autostart or systemd --user) ──
+
+const DESKTOP_FILE_NAME: &str = "remote-clauding-agent.desktop";
+const SYSTEMD_UNIT_NAME: &str = "remote-clauding-agent.service";
+
+#[derive(Serialize, Clone, Copy, PartialEq, Eq, Debug)]
+#[serde(rename_all = "lowercase")]
+pub enum AutostartMethod {
+    Xdg,
+    Systemd,
+}
+
+impl AutostartMethod {
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value {
+            "xdg" => Ok(AutostartMethod::Xdg),
+            "systemd" => Ok(AutostartMethod::Systemd),
+            other => Err(format!("Unknown autostart method: {}", other)),
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct AutostartStatus {
+    pub enabled: bool,
+    pub method: Option<AutostartMethod>,
+    pub path: Option<String>,
+}
+
+/// The command line the login entry runs.
+pub struct AgentLaunch {
+    pub program: String,
+    pub args: Vec<String>,
+    /// Prepended to PATH, e.g. the portable node's bin dir so the CLI's
+    /// `#!/usr/bin/env node` shebang resolves outside an interactive shell.
+    pub path_prefix: Option<String>,
+}
+
+/// `$XDG_CONFIG_HOME`, falling back to `<home>/.config`.
+pub fn config_home(home: &Path) -> PathBuf {
+    match std::env::var("XDG_CONFIG_HOME") {
+        Ok(xdg) if !xdg.is_empty() => PathBuf::from(xdg),
+        _ => home.join(".config"),
+    }
+}
+
+pub fn desktop_entry_path(config_home: &Path) -> PathBuf {
+    config_home.join("autostart").join(DESKTOP_FILE_NAME)
+}
+
+pub fn systemd_unit_path(config_home: &Path) -> PathBuf {
+    config_home.join("systemd").join("user").join(SYSTEMD_UNIT_NAME)
+}
+
+/// Quotes an argument for both desktop-entry `Exec=` and systemd `ExecStart=`,
+/// which share the same double-quote rules.
+fn quote_arg(arg: &str) -> String {
+    if !arg.is_empty() && !arg.chars().any(|c| c.is_whitespace() || "\"'\\$`".contains(c)) {
+        return arg.to_string();
+    }
+    let mut quoted = String::from("\"");
+    for c in arg.chars() {
+        if matches!(c, '"' | '\\' | '$' | '`') {
+            quoted.push('\\');
+        }
+        quoted.push(c);
+    }
+    quoted.push('"');
+    quoted
+}
+
+fn command_line(launch: &AgentLaunch) -> String {
+    let mut parts = vec![quote_arg(&launch.program)];
+    parts.extend(launch.args.iter().map(|a| quote_arg(a)));
+    parts.join(" ")
+}
+
+fn path_with_prefix(prefix: &str) -> String {
+    let path = std::env::var("PATH").unwrap_or_else(|_| "/usr/local/bin:/usr/bin:/bin".to_string());
+    format!("{}:{}", prefix, path)
+}
+
+pub fn desktop_entry(launch: &AgentLaunch) -> String {
+    let exec = match &launch.path_prefix {
+        Some(prefix) => format!(
+            "env {} {}",
+            quote_arg(&format!("PATH={}", path_with_prefix(prefix))),
+            command_line(launch)
+        ),
+        None => command_line(launch),
+    };
+
+    format!(
+        "[Desktop Entry]\n\
+         Type=Application\n\
+         Name=Remote Clauding Agent\n\
+         Comment=Share Claude Code sessions to your phone\n\
+         Exec={}\n\
+         Terminal=false\n\
+         NoDisplay=true\n\
+         X-GNOME-Autostart-enabled=true\n",
+        exec
+    )
+}
+
+pub fn systemd_unit(launch: &AgentLaunch) -> String {
+    let environment = match &launch.path_prefix {
+        Some(prefix) => format!("Environment={}\n", quote_arg(&format!("PATH={}", path_with_prefix(prefix)))),
+        None => String::new(),
+    };
+
+    format!(
+        "[Unit]\n\
+         Description=Remote Clauding Agent\n\
+         After=network-online.target\n\
+         \n\
+         [Service]\n\
+         Type=simple\n\
+         {}ExecStart={}\n\
+         Restart=on-failure\n\
+         RestartSec=5\n\
+         \n\
+         [Install]\n\
+         WantedBy=default.target\n",
+        environment,
+        command_line(launch)
+    )
+}
+
+fn write_file(path: &Path, contents: &str) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Cannot create {}: {}", parent.display(), e))?;
+    }
+    fs::write(path, contents).map_err(|e| format!("Cannot write {}: {}", path.display(), e))
+}
+
+fn systemctl_user(args: &[&str]) -> Result<(), String> {
+    let output = Command::new("systemctl")
+        .arg("--user")
+        .args(args)
+        .output()
+        .map_err(|e| format!("systemctl failed: {}", e))?;
+
+    if !output.status.success() {
+        return Err(format!(
+            "systemctl --user {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        ));
+    }
+    Ok(())
+}
+
+/// Writes the login entry for `method`, removing the other kind so only one
+/// launcher exists. Does not talk to systemd; see `enable`.
+pub fn install_files(config_home: &Path, method: AutostartMethod, launch: &AgentLaunch) -> Result<PathBuf, String> {
+    let (path, contents, other) = match method {
+        AutostartMethod::Xdg => (desktop_entry_path(config_home), desktop_entry(launch), systemd_unit_path(config_home)),
+        AutostartMethod::Systemd => (systemd_unit_path(config_home), systemd_unit(launch), desktop_entry_path(config_home)),
+    };
+
+    write_file(&path, &contents)?;
+    if other.exists() {
+        let _ = fs::remove_file(&other);
+    }
+    Ok(path)
+}
+
+pub fn enable(config_home: &Path, method: AutostartMethod, launch: &AgentLaunch) -> Result<AutostartStatus, String> {
+    // Drop a previously enabled unit before switching to the desktop entry
+    if method == AutostartMethod::Xdg && systemd_unit_path(config_home).exists() {
+        let _ = systemctl_user(&["disable", SYSTEMD_UNIT_NAME]);
+    }
+
+    let path = install_files(config_home, method, launch)?;
+
+    if method == AutostartMethod::Systemd {
+        systemctl_user(&["daemon-reload"])?;
+        systemctl_user(&["enable", SYSTEMD_UNIT_NAME])?;
+    }
+
+    Ok(AutostartStatus {
+        enabled: true,
+        method: Some(method),
+        path: Some(path.to_string_lossy().to_string()),
+    })
+}
+
+pub fn disable(config_home: &Path) -> Result<(), String> {
+    let unit = systemd_unit_path(config_home);
+    if unit.exists() {
+        let _ = systemctl_user(&["disable", SYSTEMD_UNIT_NAME]);
+        fs::remove_file(&unit).map_err(|e| format!("Cannot remove {}: {}", unit.display(), e))?;
+        let _ = systemctl_user(&["daemon-reload"]);
+    }
+
+    let desktop = desktop_entry_path(config_home);
+    if desktop.exists() {
+        fs::remove_file(&desktop).map_err(|e| format!("Cannot remove {}: {}", desktop.display(), e))?;
+    }
+
+    Ok(())
+}
+
+pub fn status(config_home: &Path) -> AutostartStatus {
+    for (method, path) in [
+        (AutostartMethod::Systemd, systemd_unit_path(config_home)),
+        (AutostartMethod::Xdg, desktop_entry_path(config_home)),
+    ] {
+        if path.exists() {
+            return AutostartStatus {
+                enabled: true,
+                method: Some(method),
+                path: Some(path.to_string_lossy().to_string()),
+            };
+        }
+    }
+
+    AutostartStatus {
+        enabled: false,
+        method: None,
+        path: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A fresh fake `$HOME` under the system temp dir.
+    fn temp_home(name: &str) -> PathBuf {
+        let home = std::env::temp_dir().join(format!("rc-autostart-{}-{}", std::process::id(), name));
+        let _ = fs::remove_dir_all(&home);
+        fs::create_dir_all(&home).unwrap();
+        home
+    }
+
+    fn launch(path_prefix: Option<&str>) -> AgentLaunch {
+        AgentLaunch {
+            program: "/opt/Remote Clauding/bin/remote-clauding".to_string(),
+            args: vec!["start".to_string(), "--port".to_string(), "9680".to_string()],
+            path_prefix: path_prefix.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn quotes_only_when_needed() {
+        assert_eq!(quote_arg("start"), "start");
+        assert_eq!(quote_arg(""), "\"\"");
+        assert_eq!(quote_arg("a b"), "\"a b\"");
+        assert_eq!(quote_arg("$HOME\"`\\"), "\"\\$HOME\\\"\\`\\\\\"");
+    }
+
+    #[test]
+    fn installs_desktop_entry() {
+        let home = temp_home("xdg");
+        let config_home = home.join(".config");
+
+        let path = install_files(&config_home, AutostartMethod::Xdg, &launch(None)).unwrap();
+        assert_eq!(path, config_home.join("autostart").join(DESKTOP_FILE_NAME));
+
+        let contents = fs::read_to_string(&path).unwrap();
+        assert!(contents.starts_with("[Desktop Entry]\nType=Application\n"));
+        assert!(contents.contains("\nExec=\"/opt/Remote Clauding/bin/remote-clauding\" start --port 9680\n"));
+        assert!(contents.contains("\nTerminal=false\n"));
+
+        let status = status(&config_home);
+        assert!(status.enabled);
+        assert_eq!(status.method, Some(AutostartMethod::Xdg));
+        let _ = fs::remove_dir_all(&home);
+    }
+
+    #[test]
+    fn installs_systemd_unit_with_path_prefix() {
+        let home = temp_home("systemd");
+        let config_home = home.join(".config");
+
+        let path = install_files(&config_home, AutostartMethod::Systemd, &launch(Some("/home/u/node/bin"))).unwrap();
+        assert_eq!(path, config_home.join("systemd").join("user").join(SYSTEMD_UNIT_NAME));
+
+        let contents = fs::read_to_string(&path).unwrap();
+        assert!(contents.starts_with("[Unit]\nDescription=Remote Clauding Agent\n"));
+        // PATH is quoted when the inherited value has spaces
+        let environment = contents.lines().find(|l| l.starts_with("Environment=")).unwrap();
+        assert!(environment.trim_start_matches("Environment=").trim_start_matches('"').starts_with("PATH=/home/u/node/bin:"));
+        assert!(contents.contains("\nExecStart=\"/opt/Remote Clauding/bin/remote-clauding\" start --port 9680\n"));
+        assert!(contents.ends_with("[Install]\nWantedBy=default.target\n"));
+        let _ = fs::remove_dir_all(&home);
+    }
+
+    #[test]
+    fn switching_method_removes_the_other_launcher() {
+        let home = temp_home("switch");
+        let config_home = home.join(".config");
+
+        install_files(&config_home, AutostartMethod::Xdg, &launch(None)).unwrap();
+        install_files(&config_home, AutostartMethod::Systemd, &launch(None)).unwrap();
+        assert!(!desktop_entry_path(&config_home).exists());
+        assert_eq!(status(&config_home).method, Some(AutostartMethod::Systemd));
+
+        install_files(&config_home, AutostartMethod::Xdg, &launch(None)).unwrap();
+        assert!(!systemd_unit_path(&config_home).exists());
+        assert_eq!(status(&config_home).method, Some(AutostartMethod::Xdg));
+        let _ = fs::remove_dir_all(&home);
+    }
+
+    #[test]
+    fn status_without_launcher() {
+        let home = temp_home("none");
+        let status = status(&home.join(".config"));
+        assert!(!status.enabled);
+        assert!(status.method.is_none());
+        let _ = fs::remove_dir_all(&home);
+    }
+}
@@ -0,0 +1,68 @@
+use serde::Serialize;
+
+// ── Stable identity of this machine towards the relay ──
+
+/// Sent with login and agent start so the relay can issue a per-device
+/// token and label sessions with the machine they come from.
+#[derive(Serialize, Clone)]
+pub struct DeviceIdentity {
+    pub device_id: String,
+    pub device_name: String,
+}
+
+const MAX_NAME_LEN: usize = 64;
+
+fn default_name() -> String {
+    hostname::get()
+        .ok()
+        .and_then(|h| h.into_string().ok())
+        .filter(|h| !h.is_empty())
+        .unwrap_or_else(|| "Desktop".to_string())
+}
+
+pub fn clean_name(name: &str) -> Result<String, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Device name cannot be empty".to_string());
+    }
+    Ok(name.chars().take(MAX_NAME_LEN).collect())
+}
+
+/// The device ID and name from config.json, generating and saving them on
+/// first use. The agent reads the same keys.
+pub fn identity() -> DeviceIdentity {
+    let mut config = crate::read_config();
+    let mut changed = false;
+    let device_id = match config.device_id.clone() {
+        Some(id) if !id.is_empty() => id,
+        _ => {
+            changed = true;
+            uuid::Uuid::new_v4().to_string()
+        }
+    };
+    let device_name = match config.device_name.clone() {
+        Some(name) if !name.is_empty() => name,
+        _ => {
+            changed = true;
+            default_name()
+        }
+    };
+    if changed {
+        config.device_id = Some(device_id.clone());
+        config.device_name = Some(device_name.clone());
+        crate::save_config(&config);
+    }
+    DeviceIdentity { device_id, device_name }
+}
+
+pub fn set_name(name: &str) -> Result<DeviceIdentity, String> {
+    let name = clean_name(name)?;
+    let identity = identity();
+    let mut config = crate::read_config();
+    config.device_name = Some(name.clone());
+    crate::save_config(&config);
+    Ok(DeviceIdentity {
+        device_name: name,
+        ..identity
+    })
+}
@@ -0,0 +1,67 @@
+use std::time::{Duration, Instant};
+use tauri::Emitter;
+use tauri_plugin_shell::ShellExt;
+
+use crate::device::DeviceIdentity;
+use crate::relay_client::{AuthResponse, RelayClient, RelayError};
+
+// ── Browser sign-in via the relay's device-authorization grant ──
+
+/// Added to the poll interval each time the relay answers `slow_down`
+const SLOW_DOWN_SECS: u64 = 5;
+
+/// Turns the grant's error codes into messages for the Login page.
+fn grant_error(status: u16, code: &str) -> RelayError {
+    let message = match code {
+        "access_denied" => "Sign-in was denied in the browser",
+        "expired_token" => "The sign-in code expired, please try again",
+        other => other,
+    };
+    RelayError::Server {
+        status,
+        message: message.to_string(),
+    }
+}
+
+/// Requests a user code, opens the verification page in the browser and
+/// polls until the user approves or denies it there, or the code expires.
+/// Emits `device_login_code` so the UI can show the code meanwhile.
+pub async fn authorize(app: &tauri::AppHandle, relay: &RelayClient, device: &DeviceIdentity) -> Result<AuthResponse, RelayError> {
+    let code = relay.request_device_code(device).await?;
+    let url = code
+        .verification_uri_complete
+        .clone()
+        .unwrap_or_else(|| code.verification_uri.clone());
+    let _ = app.emit(
+        "device_login_code",
+        serde_json::json!({
+            "user_code": code.user_code,
+            "verification_uri": code.verification_uri,
+            "expires_in": code.expires_in,
+        }),
+    );
+    // Shell::open is deprecated in favour of tauri-plugin-opener, which we don't ship
+    #[allow(deprecated)]
+    if let Err(e) = app.shell().open(url, None) {
+        // The UI shows the URL and code, so the user can still open it by hand
+        log::warn!("Cannot open browser for sign-in: {}", e);
+    }
+
+    let deadline = Instant::now() + Duration::from_secs(code.expires_in);
+    let mut interval = code.interval.max(1);
+    loop {
+        tokio::time::sleep(Duration::from_secs(interval)).await;
+        if Instant::now() >= deadline {
+            return Err(grant_error(400, "expired_token"));
+        }
+        match relay.poll_device_token(&code.device_code).await {
+            Ok(response) => return Ok(response),
+            Err(RelayError::Server { message, .. }) if message == "authorization_pending" => {}
+            Err(RelayError::Server { message, .. }) if message == "slow_down" => interval += SLOW_DOWN_SECS,
+            Err(RelayError::Server { status, message }) => return Err(grant_error(status, &message)),
+            // Keep polling through network blips until the code expires
+            Err(RelayError::Network(e)) => log::warn!("Sign-in poll failed: {}", e),
+            Err(e) => return Err(e),
+        }
+    }
+}
@@ -0,0 +1,443 @@
+use rusqlite::{params, params_from_iter, Connection};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tauri::Manager;
+
+use crate::session_events::{AgentEvent, SessionEvents};
+
+// ── Local searchable history of session output ──
+
+const DB_FILE: &str = "history.db";
+/// Retention is enforced on startup and then every this many inserts
+const PRUNE_INTERVAL: u64 = 500;
+const DEFAULT_SEARCH_LIMIT: u32 = 100;
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS events (
+    id INTEGER PRIMARY KEY,
+    session_id TEXT NOT NULL,
+    project_name TEXT NOT NULL DEFAULT '',
+    project_path TEXT NOT NULL DEFAULT '',
+    kind TEXT NOT NULL,
+    timestamp INTEGER NOT NULL,
+    content TEXT NOT NULL DEFAULT '',
+    message TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS events_session ON events(session_id, id);
+CREATE INDEX IF NOT EXISTS events_project_time ON events(project_path, timestamp);
+CREATE VIRTUAL TABLE IF NOT EXISTS events_fts USING fts5(content, content='events', content_rowid='id');
+CREATE TRIGGER IF NOT EXISTS events_ai AFTER INSERT ON events BEGIN
+    INSERT INTO events_fts(rowid, content) VALUES (new.id, new.content);
+END;
+CREATE TRIGGER IF NOT EXISTS events_ad AFTER DELETE ON events BEGIN
+    INSERT INTO events_fts(events_fts, rowid, content) VALUES ('delete', old.id, old.content);
+END;
+";
+
+/// Limits applied to the history database; `None` means unlimited.
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct HistoryRetention {
+    #[serde(default)]
+    pub max_age_days: Option<u32>,
+    #[serde(default)]
+    pub max_entries: Option<u64>,
+}
+
+#[derive(Deserialize, Default)]
+pub struct HistoryQuery {
+    /// Full-text terms; all must match
+    #[serde(default)]
+    pub text: Option<String>,
+    #[serde(default)]
+    pub project_path: Option<String>,
+    /// Epoch milliseconds, inclusive
+    #[serde(default)]
+    pub from: Option<u64>,
+    /// Epoch milliseconds, exclusive
+    #[serde(default)]
+    pub to: Option<u64>,
+    #[serde(default)]
+    pub limit: Option<u32>,
+}
+
+#[derive(Serialize)]
+pub struct HistoryEntry {
+    pub id: i64,
+    pub session_id: String,
+    pub project_name: String,
+    pub project_path: String,
+    pub kind: String,
+    pub timestamp: u64,
+    pub content: String,
+    pub message: serde_json::Value,
+}
+
+#[derive(Serialize)]
+pub struct HistorySession {
+    pub session_id: String,
+    pub project_name: String,
+    pub project_path: String,
+    pub first_timestamp: u64,
+    pub last_timestamp: u64,
+    pub event_count: u64,
+}
+
+/// Text indexed for a `claude_output` message.
+fn searchable_text(message: &serde_json::Value) -> String {
+    let mut parts = Vec::new();
+    for key in ["content", "summary", "toolName"] {
+        match &message[key] {
+            serde_json::Value::String(s) => parts.push(s.clone()),
+            serde_json::Value::Null => {}
+            other => parts.push(other.to_string()),
+        }
+    }
+    if !message["toolInput"].is_null() {
+        parts.push(message["toolInput"].to_string());
+    }
+    if let Some(questions) = message["questions"].as_array() {
+        parts.extend(questions.iter().filter_map(|q| q["question"].as_str().map(|s| s.to_string())));
+    }
+    parts.join("\n")
+}
+
+/// Quotes each term so user input can't trip FTS5 query syntax.
+fn fts_query(text: &str) -> String {
+    text.split_whitespace()
+        .map(|term| format!("\"{}\"", term.replace('"', "\"\"")))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn entry_from_row(row: &rusqlite::Row) -> rusqlite::Result<HistoryEntry> {
+    let message: String = row.get(7)?;
+    Ok(HistoryEntry {
+        id: row.get(0)?,
+        session_id: row.get(1)?,
+        project_name: row.get(2)?,
+        project_path: row.get(3)?,
+        kind: row.get(4)?,
+        timestamp: row.get::<_, i64>(5)? as u64,
+        content: row.get(6)?,
+        message: serde_json::from_str(&message).unwrap_or_default(),
+    })
+}
+
+/// Streamed assistant text not yet written, per session.
+struct PendingText {
+    project_name: String,
+    project_path: String,
+    timestamp: u64,
+    text: String,
+}
+
+pub struct HistoryStore {
+    conn: Mutex<Connection>,
+    inserts: Mutex<u64>,
+    pending: Mutex<HashMap<String, PendingText>>,
+    /// Limits for the periodic prune; kept here so inserts don't read config.json
+    retention: Mutex<HistoryRetention>,
+}
+
+impl HistoryStore {
+    pub fn open(retention: HistoryRetention) -> Result<Self, String> {
+        crate::ensure_config_dir();
+        let conn = Connection::open(crate::get_config_dir().join(DB_FILE))
+            .map_err(|e| format!("Cannot open history database: {}", e))?;
+        conn.execute_batch(SCHEMA)
+            .map_err(|e| format!("Cannot create history database: {}", e))?;
+
+        let store = HistoryStore {
+            conn: Mutex::new(conn),
+            inserts: Mutex::new(0),
+            pending: Mutex::new(HashMap::new()),
+            retention: Mutex::new(retention.clone()),
+        };
+        store.prune(&retention)?;
+        Ok(store)
+    }
+
+    fn with_conn<T>(&self, f: impl FnOnce(&Connection) -> rusqlite::Result<T>) -> Result<T, String> {
+        let conn = self.conn.lock().map_err(|_| "History database is unavailable".to_string())?;
+        f(&conn).map_err(|e| format!("History database error: {}", e))
+    }
+
+    pub fn record(&self, session_id: &str, project_name: &str, project_path: &str, message: &serde_json::Value) -> Result<(), String> {
+        let kind = message["type"]
+            .as_str()
+            .or_else(|| message["role"].as_str())
+            .unwrap_or("unknown");
+        let timestamp = message["timestamp"].as_u64().unwrap_or_else(crate::now_millis);
+
+        self.with_conn(|conn| {
+            conn.execute(
+                "INSERT INTO events (session_id, project_name, project_path, kind, timestamp, content, message)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                params![
+                    session_id,
+                    project_name,
+                    project_path,
+                    kind,
+                    timestamp as i64,
+                    searchable_text(message),
+                    message.to_string()
+                ],
+            )
+        })?;
+
+        let due = self
+            .inserts
+            .lock()
+            .map(|mut n| {
+                *n += 1;
+                *n % PRUNE_INTERVAL == 0
+            })
+            .unwrap_or(false);
+        if due {
+            let retention = self.retention.lock().map(|r| r.clone()).unwrap_or_default();
+            self.prune(&retention)?;
+        }
+        Ok(())
+    }
+
+    pub fn search(&self, query: &HistoryQuery) -> Result<Vec<HistoryEntry>, String> {
+        let mut sql = String::from(
+            "SELECT e.id, e.session_id, e.project_name, e.project_path, e.kind, e.timestamp, e.content, e.message
+             FROM events e",
+        );
+        let mut conditions = Vec::new();
+        let mut values: Vec<rusqlite::types::Value> = Vec::new();
+
+        if let Some(text) = query.text.as_deref().filter(|t| !t.trim().is_empty()) {
+            sql.push_str(" JOIN events_fts f ON f.rowid = e.id");
+            conditions.push("events_fts MATCH ?");
+            values.push(fts_query(text).into());
+        }
+        if let Some(project_path) = &query.project_path {
+            conditions.push("e.project_path = ?");
+            values.push(project_path.clone().into());
+        }
+        if let Some(from) = query.from {
+            conditions.push("e.timestamp >= ?");
+            values.push((from as i64).into());
+        }
+        if let Some(to) = query.to {
+            conditions.push("e.timestamp < ?");
+            values.push((to as i64).into());
+        }
+        if !conditions.is_empty() {
+            sql.push_str(" WHERE ");
+            sql.push_str(&conditions.join(" AND "));
+        }
+        sql.push_str(" ORDER BY e.timestamp DESC, e.id DESC LIMIT ?");
+        values.push((query.limit.unwrap_or(DEFAULT_SEARCH_LIMIT) as i64).into());
+
+        self.with_conn(|conn| {
+            let mut stmt = conn.prepare(&sql)?;
+            let rows = stmt.query_map(params_from_iter(values), entry_from_row)?;
+            rows.collect()
+        })
+    }
+
+    /// Sessions with stored history, most recently active first.
+    pub fn sessions(&self, project_path: Option<&str>) -> Result<Vec<HistorySession>, String> {
+        self.with_conn(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT session_id, MAX(project_name), MAX(project_path), MIN(timestamp), MAX(timestamp), COUNT(*)
+                 FROM events WHERE ?1 IS NULL OR project_path = ?1
+                 GROUP BY session_id ORDER BY MAX(timestamp) DESC",
+            )?;
+            let rows = stmt.query_map(params![project_path], |row| {
+                Ok(HistorySession {
+                    session_id: row.get(0)?,
+                    project_name: row.get(1)?,
+                    project_path: row.get(2)?,
+                    first_timestamp: row.get::<_, i64>(3)? as u64,
+                    last_timestamp: row.get::<_, i64>(4)? as u64,
+                    event_count: row.get::<_, i64>(5)? as u64,
+                })
+            })?;
+            rows.collect()
+        })
+    }
+
+    /// Stored messages of one session, oldest first.
+    pub fn session_messages(&self, session_id: &str) -> Result<Vec<serde_json::Value>, String> {
+        self.with_conn(|conn| {
+            let mut stmt = conn.prepare("SELECT message FROM events WHERE session_id = ?1 ORDER BY id")?;
+            let rows = stmt.query_map(params![session_id], |row| row.get::<_, String>(0))?;
+            rows.map(|r| r.map(|m| serde_json::from_str(&m).unwrap_or_default()))
+                .collect()
+        })
+    }
+
+    /// Makes `retention` the limits for later prunes and applies them now;
+    /// returns the number of entries removed.
+    pub fn set_retention(&self, retention: HistoryRetention) -> Result<u64, String> {
+        if let Ok(mut current) = self.retention.lock() {
+            *current = retention.clone();
+        }
+        self.prune(&retention)
+    }
+
+    /// Deletes entries beyond the retention limits; returns how many.
+    pub fn prune(&self, retention: &HistoryRetention) -> Result<u64, String> {
+        self.with_conn(|conn| {
+            let mut removed = 0;
+            if let Some(days) = retention.max_age_days {
+                let cutoff = crate::now_millis().saturating_sub(days as u64 * 24 * 60 * 60 * 1000);
+                removed += conn.execute("DELETE FROM events WHERE timestamp < ?1", params![cutoff as i64])?;
+            }
+            if let Some(max) = retention.max_entries {
+                removed += conn.execute(
+                    "DELETE FROM events WHERE id NOT IN (SELECT id FROM events ORDER BY id DESC LIMIT ?1)",
+                    params![max as i64],
+                )?;
+            }
+            Ok(removed as u64)
+        })
+    }
+
+    pub fn clear(&self, session_id: Option<&str>) -> Result<u64, String> {
+        self.with_conn(|conn| {
+            let removed = conn.execute(
+                "DELETE FROM events WHERE ?1 IS NULL OR session_id = ?1",
+                params![session_id],
+            )?;
+            Ok(removed as u64)
+        })
+    }
+
+    /// Records one `claude_output` message. Streamed text is collected and
+    /// stored as a single `assistant_message` once its block ends, and tool
+    /// input deltas are dropped (the complete input follows in
+    /// `tool_use_update`), so each search hit is a whole message.
+    pub fn record_output(&self, session_id: &str, project_name: &str, project_path: &str, message: &serde_json::Value) -> Result<(), String> {
+        match message["type"].as_str() {
+            Some("tool_use_delta") => Ok(()),
+            Some("assistant_delta") => {
+                let chunk = message["content"].as_str().unwrap_or_default();
+                let mut pending = self.pending.lock().map_err(|_| "History buffer is unavailable".to_string())?;
+                pending
+                    .entry(session_id.to_string())
+                    .or_insert_with(|| PendingText {
+                        project_name: project_name.to_string(),
+                        project_path: project_path.to_string(),
+                        timestamp: message["timestamp"].as_u64().unwrap_or_else(crate::now_millis),
+                        text: String::new(),
+                    })
+                    .text
+                    .push_str(chunk);
+                Ok(())
+            }
+            // Only marks where streamed text starts; nothing to search
+            Some("text_block_start") => self.flush_pending(session_id),
+            _ => {
+                self.flush_pending(session_id)?;
+                self.record(session_id, project_name, project_path, message)
+            }
+        }
+    }
+
+    fn flush_pending(&self, session_id: &str) -> Result<(), String> {
+        let pending = self
+            .pending
+            .lock()
+            .map_err(|_| "History buffer is unavailable".to_string())?
+            .remove(session_id);
+        match pending {
+            Some(p) if !p.text.is_empty() => {
+                let message = serde_json::json!({
+                    "type": "assistant_message",
+                    "role": "assistant",
+                    "content": p.text,
+                    "timestamp": p.timestamp,
+                });
+                self.record(session_id, &p.project_name, &p.project_path, &message)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    pub fn handle(&self, app: &tauri::AppHandle, event: &AgentEvent) {
+        let AgentEvent::ClaudeOutput { session_id, message } = event else {
+            return;
+        };
+
+        let (project_name, project_path) = app
+            .try_state::<Arc<SessionEvents>>()
+            .and_then(|events| events.session(session_id))
+            .map(|s| (s.session.project_name, s.session.project_path))
+            .unwrap_or_default();
+
+        if let Err(e) = self.record_output(session_id, &project_name, &project_path, message) {
+            log::warn!("Cannot record session history: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn store() -> HistoryStore {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(SCHEMA).unwrap();
+        HistoryStore {
+            conn: Mutex::new(conn),
+            inserts: Mutex::new(0),
+            pending: Mutex::new(HashMap::new()),
+            retention: Mutex::new(HistoryRetention::default()),
+        }
+    }
+
+    fn record(store: &HistoryStore, session_id: &str, message: serde_json::Value) {
+        store.record_output(session_id, "demo", "/home/u/demo", &message).unwrap();
+    }
+
+    fn search(store: &HistoryStore, text: &str) -> Vec<HistoryEntry> {
+        store
+            .search(&HistoryQuery {
+                text: Some(text.to_string()),
+                ..Default::default()
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn streamed_text_is_stored_as_one_message() {
+        let store = store();
+        record(&store, "s1", json!({ "role": "user", "content": "hello", "timestamp": 1u64 }));
+        record(&store, "s1", json!({ "type": "text_block_start" }));
+        record(&store, "s1", json!({ "type": "assistant_delta", "content": "The quick ", "timestamp": 2u64 }));
+        record(&store, "s1", json!({ "type": "assistant_delta", "content": "brown fox", "timestamp": 3u64 }));
+        record(&store, "s1", json!({ "type": "tool_use_start", "toolId": "t1", "toolName": "Read", "timestamp": 4u64 }));
+        record(&store, "s1", json!({ "type": "tool_use_delta", "content": "{\"file_pa" }));
+
+        let messages = store.session_messages("s1").unwrap();
+        let kinds: Vec<&str> = messages.iter().map(|m| m["type"].as_str().unwrap_or("user")).collect();
+        assert_eq!(kinds, ["user", "assistant_message", "tool_use_start"]);
+        assert_eq!(messages[1]["content"], "The quick brown fox");
+        assert_eq!(messages[1]["timestamp"], 2);
+
+        let hits = search(&store, "quick fox");
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].kind, "assistant_message");
+    }
+
+    #[test]
+    fn pending_text_is_kept_per_session() {
+        let store = store();
+        record(&store, "s1", json!({ "type": "assistant_delta", "content": "alpha" }));
+        record(&store, "s2", json!({ "type": "assistant_delta", "content": "beta" }));
+        record(&store, "s1", json!({ "type": "result" }));
+
+        assert_eq!(search(&store, "alpha").len(), 1);
+        assert!(search(&store, "beta").is_empty());
+
+        record(&store, "s2", json!({ "type": "text_block_start" }));
+        assert_eq!(search(&store, "beta")[0].session_id, "s2");
+    }
+}
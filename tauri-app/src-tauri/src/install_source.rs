@@ -0,0 +1,280 @@
+use serde::{Deserialize, Serialize};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+// ── Where the CLI package is installed from, and the record of it ──
+
+/// npm name of the CLI; anything else is refused
+pub const PACKAGE_NAME: &str = "remote-clauding";
+const MANIFEST_FILE: &str = "install.json";
+
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum InstallSource {
+    /// The package shipped in the app's resources
+    Bundled,
+    /// A `.tgz` produced by `npm pack`
+    Tarball { path: String },
+    /// A git repository of the CLI, optionally at a branch, tag or commit
+    Git {
+        url: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        git_ref: Option<String>,
+    },
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct PackageInfo {
+    pub name: String,
+    pub version: String,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct InstallManifest {
+    pub source: InstallSource,
+    pub package: PackageInfo,
+    /// Unix time in milliseconds
+    pub installed_at: u64,
+}
+
+fn is_numeric_identifier(part: &str) -> bool {
+    !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit()) && (part == "0" || !part.starts_with('0'))
+}
+
+fn is_identifier(part: &str) -> bool {
+    !part.is_empty() && part.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-')
+}
+
+/// Accepts semver `MAJOR.MINOR.PATCH[-pre][+build]`.
+pub fn is_valid_version(version: &str) -> bool {
+    let (version, build) = match version.split_once('+') {
+        Some((v, b)) => (v, Some(b)),
+        None => (version, None),
+    };
+    let (core, pre) = match version.split_once('-') {
+        Some((c, p)) => (c, Some(p)),
+        None => (version, None),
+    };
+    let core: Vec<&str> = core.split('.').collect();
+    core.len() == 3
+        && core.iter().all(|p| is_numeric_identifier(p))
+        && pre.map_or(true, |p| p.split('.').all(is_identifier))
+        && build.map_or(true, |b| b.split('.').all(is_identifier))
+}
+
+pub fn validate_package(info: &PackageInfo) -> Result<(), String> {
+    if info.name != PACKAGE_NAME {
+        return Err(format!("Expected package {}, found {}", PACKAGE_NAME, info.name));
+    }
+    if !is_valid_version(&info.version) {
+        return Err(format!("Invalid package version: {}", info.version));
+    }
+    Ok(())
+}
+
+fn parse_package_json(data: &str) -> Result<PackageInfo, String> {
+    let json: serde_json::Value = serde_json::from_str(data).map_err(|e| format!("Invalid package.json: {}", e))?;
+    Ok(PackageInfo {
+        name: json["name"].as_str().unwrap_or_default().to_string(),
+        version: json["version"].as_str().unwrap_or_default().to_string(),
+    })
+}
+
+pub fn read_dir_package(dir: &Path) -> Result<PackageInfo, String> {
+    let path = dir.join("package.json");
+    let data = std::fs::read_to_string(&path).map_err(|e| format!("Cannot read {}: {}", path.display(), e))?;
+    parse_package_json(&data)
+}
+
+/// Reads `package/package.json` out of an `npm pack` tarball.
+pub fn read_tarball_package(archive: &Path) -> Result<PackageInfo, String> {
+    let file = std::fs::File::open(archive).map_err(|e| format!("Cannot open {}: {}", archive.display(), e))?;
+    let mut tar = tar::Archive::new(flate2::read::GzDecoder::new(file));
+    for entry in tar.entries().map_err(|e| format!("Not a package tarball: {}", e))? {
+        let mut entry = entry.map_err(|e| format!("Not a package tarball: {}", e))?;
+        let is_manifest = entry
+            .path()
+            .map(|p| p == Path::new("package/package.json"))
+            .unwrap_or(false);
+        if is_manifest {
+            let mut data = String::new();
+            entry
+                .read_to_string(&mut data)
+                .map_err(|e| format!("Cannot read package.json: {}", e))?;
+            return parse_package_json(&data);
+        }
+    }
+    Err(format!("{} has no package/package.json", archive.display()))
+}
+
+pub fn tarball_path(path: &str) -> Result<PathBuf, String> {
+    let path = PathBuf::from(path.trim());
+    let name = path.file_name().map(|n| n.to_string_lossy().to_lowercase()).unwrap_or_default();
+    if !(name.ends_with(".tgz") || name.ends_with(".tar.gz")) {
+        return Err(format!("Not a .tgz file: {}", path.display()));
+    }
+    if !path.is_file() {
+        return Err(format!("File not found: {}", path.display()));
+    }
+    Ok(path)
+}
+
+/// The npm spec for a git source, e.g. `git+https://host/repo.git#v1.2.0`.
+pub fn git_spec(url: &str, git_ref: Option<&str>) -> Result<String, String> {
+    let url = url.trim();
+    let spec = if url.starts_with("git+https://") || url.starts_with("git+ssh://") || url.starts_with("github:") {
+        url.to_string()
+    } else if url.starts_with("https://") || url.starts_with("ssh://") {
+        format!("git+{}", url)
+    } else {
+        return Err(format!("Unsupported git URL (use https, ssh or github:): {}", url));
+    };
+    if spec.contains('#') || spec.chars().any(char::is_whitespace) {
+        return Err(format!("Invalid git URL: {}", url));
+    }
+
+    match git_ref.map(str::trim).filter(|r| !r.is_empty()) {
+        None => Ok(spec),
+        Some(r) => {
+            let valid = !r.starts_with('-')
+                && !r.contains("..")
+                && r.chars().all(|c| c.is_ascii_alphanumeric() || "._/-".contains(c));
+            if !valid {
+                return Err(format!("Invalid git ref: {}", r));
+            }
+            Ok(format!("{}#{}", spec, r))
+        }
+    }
+}
+
+pub fn read_manifest() -> Option<InstallManifest> {
+    let data = std::fs::read_to_string(crate::get_config_dir().join(MANIFEST_FILE)).ok()?;
+    serde_json::from_str(&data).ok()
+}
+
+pub fn save_manifest(manifest: &InstallManifest) -> Result<(), String> {
+    crate::ensure_config_dir();
+    let json = serde_json::to_string_pretty(manifest).map_err(|e| e.to_string())?;
+    std::fs::write(crate::get_config_dir().join(MANIFEST_FILE), json)
+        .map_err(|e| format!("Cannot write install manifest: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Writes a gzipped tarball holding `files` and returns its path.
+    fn tarball(name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("rc-install-{}-{}.tgz", std::process::id(), name));
+        let gz = flate2::write::GzEncoder::new(std::fs::File::create(&path).unwrap(), flate2::Compression::default());
+        let mut builder = tar::Builder::new(gz);
+        for (entry, data) in files {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(data.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder.append_data(&mut header, entry, data.as_bytes()).unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap();
+        path
+    }
+
+    #[test]
+    fn versions_follow_semver() {
+        assert!(is_valid_version("1.2.3"));
+        assert!(is_valid_version("0.10.0"));
+        assert!(is_valid_version("1.2.3-beta.1"));
+        assert!(is_valid_version("1.2.3-rc-1+build.5"));
+        assert!(is_valid_version("1.2.3+20260101"));
+
+        assert!(!is_valid_version("01.2.3"));
+        assert!(!is_valid_version("1.02.3"));
+        assert!(!is_valid_version("1.2"));
+        assert!(!is_valid_version("1.2.3.4"));
+        assert!(!is_valid_version("v1.2.3"));
+        assert!(!is_valid_version("1.2.3-"));
+        assert!(!is_valid_version("1.2.3-beta..1"));
+        assert!(!is_valid_version("1.2.3+"));
+        assert!(!is_valid_version("1.2.3-beta_1"));
+        assert!(!is_valid_version(""));
+    }
+
+    #[test]
+    fn git_spec_normalises_supported_urls() {
+        assert_eq!(git_spec(" https://host/repo.git ", None).unwrap(), "git+https://host/repo.git");
+        assert_eq!(git_spec("ssh://git@host/repo.git", None).unwrap(), "git+ssh://git@host/repo.git");
+        assert_eq!(git_spec("git+https://host/repo.git", None).unwrap(), "git+https://host/repo.git");
+        assert_eq!(git_spec("github:owner/repo", Some("v1.2.0")).unwrap(), "github:owner/repo#v1.2.0");
+        assert_eq!(
+            git_spec("https://host/repo.git", Some(" feature/x ")).unwrap(),
+            "git+https://host/repo.git#feature/x"
+        );
+        assert_eq!(git_spec("https://host/repo.git", Some("  ")).unwrap(), "git+https://host/repo.git");
+    }
+
+    #[test]
+    fn git_spec_refuses_other_schemes() {
+        for url in ["http://host/repo.git", "file:///tmp/repo", "git://host/repo.git", "/tmp/repo", "repo"] {
+            assert!(git_spec(url, None).is_err(), "{} was accepted", url);
+        }
+    }
+
+    #[test]
+    fn git_spec_refuses_smuggled_refs() {
+        assert!(git_spec("https://host/repo.git#main", None).is_err());
+        assert!(git_spec("https://host/repo.git --foo", None).is_err());
+        for r in ["-b", "--upload-pack=evil", "main..other", "../../etc", "v1 2", "main;rm", "a#b"] {
+            assert!(git_spec("https://host/repo.git", Some(r)).is_err(), "{} was accepted", r);
+        }
+    }
+
+    #[test]
+    fn tarball_path_needs_an_existing_tgz() {
+        let path = tarball("path", &[]);
+        assert_eq!(tarball_path(&format!(" {} ", path.display())).unwrap(), path);
+        let missing = path.with_file_name("rc-install-missing.tgz");
+        assert!(tarball_path(&missing.display().to_string()).is_err());
+        let _ = std::fs::remove_file(&path);
+
+        assert!(tarball_path("/tmp/package.zip").is_err());
+        assert!(tarball_path("").is_err());
+    }
+
+    #[test]
+    fn reads_the_package_out_of_a_tarball() {
+        let path = tarball(
+            "package",
+            &[
+                ("package/README.md", "# readme"),
+                ("package/package.json", r#"{"name":"remote-clauding","version":"1.4.0"}"#),
+            ],
+        );
+        let info = read_tarball_package(&path);
+        let _ = std::fs::remove_file(&path);
+        let info = info.unwrap();
+        assert_eq!(info.name, PACKAGE_NAME);
+        assert_eq!(info.version, "1.4.0");
+        assert!(validate_package(&info).is_ok());
+    }
+
+    #[test]
+    fn tarball_without_manifest_is_refused() {
+        let path = tarball(
+            "no-manifest",
+            &[("other/package.json", r#"{"name":"remote-clauding","version":"1.4.0"}"#)],
+        );
+        let result = read_tarball_package(&path);
+        let _ = std::fs::remove_file(&path);
+        match result {
+            Ok(_) => panic!("a tarball without package/package.json was accepted"),
+            Err(e) => assert!(e.contains("has no package/package.json"), "{}", e),
+        }
+    }
+
+    #[test]
+    fn validate_package_checks_name_and_version() {
+        let info = |name: &str, version: &str| PackageInfo { name: name.to_string(), version: version.to_string() };
+        assert!(validate_package(&info("left-pad", "1.0.0")).is_err());
+        assert!(validate_package(&info(PACKAGE_NAME, "latest")).is_err());
+    }
+}
@@ -1,12 +1,53 @@
 use serde::{Deserialize, Serialize};
 use std::fs;
-use std::io::{Read, Write};
-use std::path::PathBuf;
+use std::io::Read;
+use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::sync::Arc;
 use tauri::{Emitter, Manager};
+use tokio_util::sync::CancellationToken;
+
+mod agent_client;
+mod approval;
+mod auto_accept;
+#[cfg(target_os = "linux")]
+mod autostart;
+mod device;
+mod device_login;
+mod history;
+mod install_source;
+mod network;
+mod npmrc;
+mod notifications;
+mod qr;
+mod relay_client;
+mod session_events;
+mod tasks;
+#[cfg(test)]
+mod test_relay;
+mod transcript;
+mod usage;
+
+use agent_client::{AgentClient, AgentCommand};
+use approval::ApprovalWatcher;
+use install_source::InstallSource;
+use relay_client::{AccountStatus, AdminUser, AuthResponse, Device, RelayClient, RelayError};
+use session_events::SessionEvents;
+use tasks::TaskManager;
 
 const RELAY_API: &str = "https://claude.iptinno.com";
-const NODE_VERSION: &str = "v22.14.0";
+/// 22.21+ so the agent honours the proxy settings via `NODE_USE_ENV_PROXY`
+const NODE_VERSION: &str = "v22.21.0";
+const DEFAULT_AGENT_PORT: u16 = 9680;
+const AGENT_READY_TIMEOUT_SECS: u64 = 20;
+const AGENT_STDERR_LIMIT: u64 = 16 * 1024;
+/// Agent stderr goes here rather than to a pipe, so the detached agent
+/// keeps a valid stderr after the app exits
+const AGENT_LOG_FILE: &str = "agent-stderr.log";
+const AGENT_PORT_SCAN_RANGE: u16 = 20;
+const MAX_RECENT_PROJECTS: usize = 10;
+const QR_MODULE_PX: u32 = 8;
+const QR_SVG_MIN_SIZE: u32 = 256;
 
 // ── Config paths (must match lib/config.js) ──
 
@@ -49,6 +90,40 @@ pub struct AppConfig {
     pub auth_token: Option<String>,
     pub email: Option<String>,
     pub environments: Option<Vec<String>>,
+    /// Local HTTP port of the agent (same key lib/agent.js reads)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub port: Option<u16>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub recent_projects: Vec<RecentProject>,
+    #[serde(default)]
+    pub notifications: notifications::NotificationSettings,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub auto_accept_policies: Vec<auto_accept::AutoAcceptPolicy>,
+    #[serde(default)]
+    pub history_retention: history::HistoryRetention,
+    /// Set when the relay rejected `auth_token`; cleared by the next login
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub needs_relogin: bool,
+    #[serde(default)]
+    pub network: network::NetworkSettings,
+    #[serde(default)]
+    pub npm_registry: npmrc::NpmRegistrySettings,
+    /// Stable ID and friendly name of this machine; lib/agent.js reads them too
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub device_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub device_name: Option<String>,
+    /// The relay's global AUTH_TOKEN, only needed for the admin commands
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub admin_token: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct RecentProject {
+    pub path: String,
+    pub name: String,
+    /// Unix time in milliseconds
+    pub last_shared: u64,
 }
 
 #[derive(Serialize, Deserialize, Clone, Default)]
@@ -71,6 +146,37 @@ pub struct HealthResult {
     pub running: bool,
 }
 
+// ── Helper: agent port ──
+
+fn get_agent_port() -> u16 {
+    read_config().port.unwrap_or(DEFAULT_AGENT_PORT)
+}
+
+fn is_port_free(port: u16) -> bool {
+    std::net::TcpListener::bind(("127.0.0.1", port)).is_ok()
+}
+
+/// Picks the port the agent should listen on. Keeps the configured port if
+/// it is free; otherwise scans upwards and falls back to an OS-assigned port.
+fn select_agent_port(preferred: u16) -> Result<u16, String> {
+    if is_port_free(preferred) {
+        return Ok(preferred);
+    }
+    for port in preferred.saturating_add(1)..preferred.saturating_add(AGENT_PORT_SCAN_RANGE) {
+        if is_port_free(port) {
+            return Ok(port);
+        }
+    }
+    std::net::TcpListener::bind(("127.0.0.1", 0))
+        .and_then(|l| l.local_addr())
+        .map(|addr| addr.port())
+        .map_err(|e| format!("No free port for agent: {}", e))
+}
+
+fn agent_is_healthy(port: u16) -> bool {
+    AgentClient::new(port).is_healthy()
+}
+
 // ── Helper: find node and npm paths ──
 
 fn get_portable_node_dir() -> PathBuf {
@@ -150,6 +256,19 @@ fn get_cli_binary(portable: bool) -> (String, Vec<String>) {
     }
 }
 
+/// Finds `name` in PATH, for launchers (systemd, XDG autostart) that don't
+/// run through the user's shell.
+#[cfg(target_os = "linux")]
+fn resolve_on_path(name: &str) -> Option<PathBuf> {
+    if name.contains('/') {
+        return Some(PathBuf::from(name));
+    }
+    let path = std::env::var_os("PATH")?;
+    std::env::split_paths(&path)
+        .map(|dir| dir.join(name))
+        .find(|candidate| candidate.is_file())
+}
+
 fn read_node_config() -> NodeConfig {
     let path = get_config_dir().join("node-config.json");
     if let Ok(data) = fs::read_to_string(&path) {
@@ -204,6 +323,16 @@ fn check_install_state() -> String {
     "installer".to_string()
 }
 
+/// `node --version` of the portable or system Node.js, e.g. "v22.14.0".
+fn node_version(portable: bool) -> Option<String> {
+    let node = if portable { get_node_binary(true) } else { "node".to_string() };
+    let output = Command::new(node).arg("--version").output().ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
 #[tauri::command]
 fn check_node() -> NodeCheckResult {
     // Try system node first
@@ -244,84 +373,123 @@ fn check_node() -> NodeCheckResult {
 }
 
 #[tauri::command]
-fn download_portable_node(window: tauri::Window) -> Result<String, String> {
+async fn download_portable_node(window: tauri::Window, tasks: tauri::State<'_, TaskManager>) -> Result<String, String> {
+    let (url, archive_name) = get_node_download_url();
+    ensure_config_dir();
+    let archive_path = get_config_dir().join(&archive_name);
+
+    let result = tasks
+        .run(&window, "download_node", |task_id, cancel| {
+            download_and_extract_node(window.clone(), task_id, cancel, url, archive_path.clone())
+        })
+        .await;
+
+    if let Err(e) = &result {
+        // Leave no partial archive behind after a failure or cancel
+        let _ = fs::remove_file(&archive_path);
+        if e == tasks::CANCELLED {
+            let _ = window.emit("install_progress", serde_json::json!({
+                "step": "download_node",
+                "status": "cancelled",
+                "message": "Node.js download cancelled."
+            }));
+        }
+    }
+    result
+}
+
+async fn download_and_extract_node(
+    window: tauri::Window,
+    task_id: String,
+    cancel: CancellationToken,
+    url: String,
+    archive_path: PathBuf,
+) -> Result<String, String> {
+    use tokio::io::AsyncWriteExt;
+
     let _ = window.emit("install_progress", serde_json::json!({
         "step": "download_node",
         "status": "started",
+        "task_id": task_id,
         "message": "Downloading Node.js..."
     }));
 
-    let (url, archive_name) = get_node_download_url();
-    let config_dir = get_config_dir();
-    ensure_config_dir();
-    let archive_path = config_dir.join(&archive_name);
     let node_dir = get_portable_node_dir();
 
     // Download
-    let client = reqwest::blocking::Client::new();
-    let mut response = client.get(&url).send().map_err(|e| format!("Download failed: {}", e))?;
+    let client = network::client_builder(&read_config().network)
+        .connect_timeout(std::time::Duration::from_secs(10))
+        .build()
+        .unwrap_or_else(|_| reqwest::Client::new());
+    let mut response = client.get(&url).send().await.map_err(|e| format!("Download failed: {}", e))?;
 
     if !response.status().is_success() {
         return Err(format!("Download failed with status: {}", response.status()));
     }
 
     let total_size = response.content_length().unwrap_or(0);
-    let mut file = fs::File::create(&archive_path).map_err(|e| format!("Cannot create file: {}", e))?;
+    let mut file = tokio::fs::File::create(&archive_path)
+        .await
+        .map_err(|e| format!("Cannot create file: {}", e))?;
     let mut downloaded: u64 = 0;
-    let mut buffer = [0u8; 65536];
 
-    loop {
-        let bytes_read = response.read(&mut buffer).map_err(|e| format!("Read error: {}", e))?;
-        if bytes_read == 0 {
-            break;
-        }
-        file.write_all(&buffer[..bytes_read]).map_err(|e| format!("Write error: {}", e))?;
-        downloaded += bytes_read as u64;
+    while let Some(chunk) = response.chunk().await.map_err(|e| format!("Read error: {}", e))? {
+        file.write_all(&chunk).await.map_err(|e| format!("Write error: {}", e))?;
+        downloaded += chunk.len() as u64;
         if total_size > 0 {
             let percent = (downloaded as f64 / total_size as f64 * 100.0) as u32;
             let _ = window.emit("install_progress", serde_json::json!({
                 "step": "download_node",
                 "status": "progress",
+                "task_id": task_id,
                 "percent": percent,
                 "message": format!("Downloading Node.js... {}%", percent)
             }));
         }
     }
+    file.flush().await.map_err(|e| format!("Write error: {}", e))?;
     drop(file);
 
     let _ = window.emit("install_progress", serde_json::json!({
         "step": "download_node",
         "status": "extracting",
+        "task_id": task_id,
         "message": "Extracting Node.js..."
     }));
 
     // Extract
-    if node_dir.exists() {
-        let _ = fs::remove_dir_all(&node_dir);
-    }
-    fs::create_dir_all(&node_dir).map_err(|e| format!("Cannot create node dir: {}", e))?;
+    blocking(move || {
+        if node_dir.exists() {
+            let _ = fs::remove_dir_all(&node_dir);
+        }
+        fs::create_dir_all(&node_dir).map_err(|e| format!("Cannot create node dir: {}", e))?;
 
-    #[cfg(target_os = "windows")]
-    {
-        extract_zip(&archive_path, &node_dir)?;
-    }
-    #[cfg(not(target_os = "windows"))]
-    {
-        extract_tar_gz(&archive_path, &node_dir)?;
-    }
+        #[cfg(target_os = "windows")]
+        let extracted = extract_zip(&archive_path, &node_dir, &cancel);
+        #[cfg(not(target_os = "windows"))]
+        let extracted = extract_tar_gz(&archive_path, &node_dir, &cancel);
+        if let Err(e) = extracted {
+            // A half-extracted runtime would pass for an installed one
+            let _ = fs::remove_dir_all(&node_dir);
+            return Err(e);
+        }
 
-    // Clean up archive
-    let _ = fs::remove_file(&archive_path);
+        // Clean up archive
+        let _ = fs::remove_file(&archive_path);
 
-    // Save node config
-    save_node_config(&NodeConfig {
-        portable: true,
-        node_path: node_dir.to_string_lossy().to_string(),
-    });
+        // Save node config
+        save_node_config(&NodeConfig {
+            portable: true,
+            node_path: node_dir.to_string_lossy().to_string(),
+        });
+        Ok(())
+    })
+    .await?;
 
     let _ = window.emit("install_progress", serde_json::json!({
         "step": "download_node",
         "status": "done",
+        "task_id": task_id,
         "message": "Node.js installed."
     }));
 
@@ -352,11 +520,11 @@ fn get_node_download_url() -> (String, String) {
 }
 
 #[cfg(target_os = "windows")]
-fn extract_zip(archive: &PathBuf, dest: &PathBuf) -> Result<(), String> {
+fn extract_zip(archive: &Path, dest: &Path, cancel: &CancellationToken) -> Result<(), String> {
     let file = fs::File::open(archive).map_err(|e| format!("Cannot open zip: {}", e))?;
     let mut archive = zip::ZipArchive::new(file).map_err(|e| format!("Invalid zip: {}", e))?;
 
-    // Find the top-level directory name (e.g., node-v22.14.0-win-x64/)
+    // Find the top-level directory name (e.g., node-v22.21.0-win-x64/)
     let top_dir = archive
         .by_index(0)
         .map_err(|e| format!("Zip error: {}", e))?
@@ -367,6 +535,9 @@ fn extract_zip(archive: &PathBuf, dest: &PathBuf) -> Result<(), String> {
         .to_string();
 
     for i in 0..archive.len() {
+        if cancel.is_cancelled() {
+            return Err(tasks::CANCELLED.to_string());
+        }
         let mut entry = archive.by_index(i).map_err(|e| format!("Zip entry error: {}", e))?;
         let name = entry.name().to_string();
 
@@ -400,12 +571,15 @@ fn extract_zip(archive: &PathBuf, dest: &PathBuf) -> Result<(), String> {
 }
 
 #[cfg(not(target_os = "windows"))]
-fn extract_tar_gz(archive: &PathBuf, dest: &PathBuf) -> Result<(), String> {
+fn extract_tar_gz(archive: &Path, dest: &Path, cancel: &CancellationToken) -> Result<(), String> {
     let file = fs::File::open(archive).map_err(|e| format!("Cannot open archive: {}", e))?;
     let gz = flate2::read::GzDecoder::new(file);
     let mut tar = tar::Archive::new(gz);
 
     for entry in tar.entries().map_err(|e| format!("Tar error: {}", e))? {
+        if cancel.is_cancelled() {
+            return Err(tasks::CANCELLED.to_string());
+        }
         let mut entry = entry.map_err(|e| format!("Tar entry error: {}", e))?;
         let path = entry.path().map_err(|e| format!("Path error: {}", e))?.to_path_buf();
 
@@ -430,17 +604,45 @@ fn extract_tar_gz(archive: &PathBuf, dest: &PathBuf) -> Result<(), String> {
     Ok(())
 }
 
+/// Installs the CLI globally, from the bundled package by default. The
+/// package name and version are checked before npm runs, and the source is
+/// recorded in the install manifest.
 #[tauri::command]
-fn install_npm_package(app_handle: tauri::AppHandle, window: tauri::Window) -> Result<String, String> {
-    let _ = window.emit("install_progress", serde_json::json!({
-        "step": "install_npm",
-        "status": "started",
-        "message": "Installing Remote Clauding..."
-    }));
+async fn install_npm_package(
+    app_handle: tauri::AppHandle,
+    window: tauri::Window,
+    tasks: tauri::State<'_, TaskManager>,
+    source: Option<InstallSource>,
+) -> Result<String, String> {
+    let source = source.unwrap_or(InstallSource::Bundled);
+    tasks
+        .run(&window, "install_npm", |task_id, _| {
+            install_npm_task(app_handle.clone(), window.clone(), task_id, source)
+        })
+        .await
+}
 
-    let node_config = read_node_config();
+/// npm with the portable/system binary, the app's .npmrc and the proxy
+/// settings. Killed if the task is cancelled.
+fn npm_command(node_config: &NodeConfig) -> Result<tokio::process::Command, String> {
     let (npm, extra_args) = get_npm_binary(node_config.portable);
+    let config = read_config();
+    let mut cmd = tokio::process::Command::new(&npm);
+    cmd.kill_on_drop(true).envs(network::child_env(&config.network));
+    for arg in &extra_args {
+        cmd.arg(arg);
+    }
+    cmd.args(npmrc::userconfig_args(&config.npm_registry)?);
+
+    #[cfg(target_os = "windows")]
+    {
+        const CREATE_NO_WINDOW: u32 = 0x08000000;
+        cmd.creation_flags(CREATE_NO_WINDOW);
+    }
+    Ok(cmd)
+}
 
+fn bundled_package_dir(app_handle: &tauri::AppHandle) -> Result<PathBuf, String> {
     // Get the bundled npm package path from Tauri resources
     let resource_dir = app_handle
         .path()
@@ -451,7 +653,7 @@ fn install_npm_package(app_handle: tauri::AppHandle, window: tauri::Window) -> R
     let package_dir = resource_dir.join("npm-package");
 
     // Fallback: if running in dev mode, use the monorepo root
-    let install_path = if package_dir.exists() {
+    Ok(if package_dir.exists() {
         package_dir
     } else {
         // Dev fallback: go up from tauri-app/src-tauri to repo root
@@ -460,12 +662,86 @@ fn install_npm_package(app_handle: tauri::AppHandle, window: tauri::Window) -> R
             .and_then(|p| p.parent())
             .map(|p| p.to_path_buf())
             .unwrap_or(package_dir)
-    };
+    })
+}
 
-    let mut cmd = Command::new(&npm);
-    for arg in &extra_args {
-        cmd.arg(arg);
+/// Packs a git source into a tarball under the config dir so it can be
+/// validated like a user-supplied one. Returns the tarball path.
+async fn pack_git_source(node_config: &NodeConfig, spec: &str) -> Result<PathBuf, String> {
+    let dest = get_config_dir().join("packs");
+    let _ = tokio::fs::remove_dir_all(&dest).await;
+    tokio::fs::create_dir_all(&dest)
+        .await
+        .map_err(|e| format!("Cannot create {}: {}", dest.display(), e))?;
+
+    let output = npm_command(node_config)?
+        .arg("pack")
+        .arg(spec)
+        .arg("--json")
+        .arg(format!("--pack-destination={}", dest.to_string_lossy()))
+        .output()
+        .await
+        .map_err(|e| format!("npm pack failed: {}", e))?;
+    if !output.status.success() {
+        return Err(format!("npm pack failed: {}", npmrc::redact(
+            &read_config().npm_registry,
+            &String::from_utf8_lossy(&output.stderr)
+        )));
     }
+
+    let packed: serde_json::Value = serde_json::from_slice(&output.stdout)
+        .map_err(|e| format!("Unexpected npm pack output: {}", e))?;
+    let filename = packed[0]["filename"]
+        .as_str()
+        .ok_or("npm pack did not report a tarball")?;
+    Ok(dest.join(filename))
+}
+
+async fn install_npm_task(
+    app_handle: tauri::AppHandle,
+    window: tauri::Window,
+    task_id: String,
+    source: InstallSource,
+) -> Result<String, String> {
+    let _ = window.emit("install_progress", serde_json::json!({
+        "step": "install_npm",
+        "status": "started",
+        "task_id": task_id,
+        "message": "Installing Remote Clauding..."
+    }));
+
+    let node_config = read_node_config();
+
+    // Resolve what to hand to `npm install` and check it is our CLI first
+    let resolved = match &source {
+        InstallSource::Bundled => bundled_package_dir(&app_handle)
+            .and_then(|dir| install_source::read_dir_package(&dir).map(|package| (dir, package))),
+        InstallSource::Tarball { path } => install_source::tarball_path(path)
+            .and_then(|path| install_source::read_tarball_package(&path).map(|package| (path, package))),
+        InstallSource::Git { url, git_ref } => match install_source::git_spec(url, git_ref.as_deref()) {
+            Ok(spec) => pack_git_source(&node_config, &spec)
+                .await
+                .and_then(|path| install_source::read_tarball_package(&path).map(|package| (path, package))),
+            Err(e) => Err(e),
+        },
+    };
+    let (install_path, package) = match resolved.and_then(|(path, package)| {
+        install_source::validate_package(&package)?;
+        Ok((path, package))
+    }) {
+        Ok(resolved) => resolved,
+        Err(e) => {
+            let _ = window.emit("install_progress", serde_json::json!({
+                "step": "install_npm",
+                "status": "error",
+                "task_id": task_id,
+                "message": e
+            }));
+            return Err(e);
+        }
+    };
+
+    let mut cmd = npm_command(&node_config)?;
     cmd.arg("install")
         .arg("-g")
         .arg(install_path.to_string_lossy().to_string());
@@ -476,41 +752,58 @@ fn install_npm_package(app_handle: tauri::AppHandle, window: tauri::Window) -> R
         cmd.arg(format!("--prefix={}", prefix.to_string_lossy()));
     }
 
-    #[cfg(target_os = "windows")]
-    {
-        use std::os::windows::process::CommandExt;
-        const CREATE_NO_WINDOW: u32 = 0x08000000;
-        cmd.creation_flags(CREATE_NO_WINDOW);
-    }
-
-    let output = cmd.output().map_err(|e| format!("npm install failed: {}", e))?;
+    let output = cmd.output().await.map_err(|e| format!("npm install failed: {}", e))?;
 
-    let stdout = String::from_utf8_lossy(&output.stdout).to_string();
-    let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+    let registry = read_config().npm_registry;
+    let stdout = npmrc::redact(&registry, &String::from_utf8_lossy(&output.stdout));
+    let stderr = npmrc::redact(&registry, &String::from_utf8_lossy(&output.stderr));
 
     if !output.status.success() {
         let _ = window.emit("install_progress", serde_json::json!({
             "step": "install_npm",
             "status": "error",
+            "task_id": task_id,
             "message": format!("npm install failed: {}", stderr)
         }));
         return Err(format!("npm install failed: {}\n{}", stderr, stdout));
     }
 
+    let manifest = install_source::InstallManifest {
+        source,
+        package: package.clone(),
+        installed_at: now_millis(),
+    };
+    if let Err(e) = install_source::save_manifest(&manifest) {
+        log::warn!("{}", e);
+    }
+
     let _ = window.emit("install_progress", serde_json::json!({
         "step": "install_npm",
         "status": "done",
-        "message": "Remote Clauding installed."
+        "task_id": task_id,
+        "message": format!("Remote Clauding {} installed.", package.version)
     }));
 
     Ok(stdout)
 }
 
 #[tauri::command]
-fn run_setup(window: tauri::Window) -> Result<String, String> {
+fn get_install_manifest() -> Option<install_source::InstallManifest> {
+    install_source::read_manifest()
+}
+
+#[tauri::command]
+async fn run_setup(window: tauri::Window, tasks: tauri::State<'_, TaskManager>) -> Result<String, String> {
+    tasks
+        .run(&window, "setup", |task_id, _| run_setup_task(window.clone(), task_id))
+        .await
+}
+
+async fn run_setup_task(window: tauri::Window, task_id: String) -> Result<String, String> {
     let _ = window.emit("install_progress", serde_json::json!({
         "step": "setup",
         "status": "started",
+        "task_id": task_id,
         "message": "Installing VSCode extension..."
     }));
 
@@ -527,23 +820,28 @@ fn run_setup(window: tauri::Window) -> Result<String, String> {
     // Run via cmd /c so that PATH (including `code`) is resolved from the shell environment
     #[cfg(target_os = "windows")]
     let output = {
-        use std::os::windows::process::CommandExt;
         const CREATE_NO_WINDOW: u32 = 0x08000000;
-        Command::new("cmd.exe")
+        tokio::process::Command::new("cmd.exe")
             .args(["/C", &cli_cmd])
+            .envs(network::child_env(&read_config().network))
             .creation_flags(CREATE_NO_WINDOW)
+            .kill_on_drop(true)
             .output()
+            .await
             .map_err(|e| format!("Setup failed: {}", e))?
     };
 
     #[cfg(not(target_os = "windows"))]
     let output = {
-        let mut cmd = Command::new(&cli);
+        let mut cmd = tokio::process::Command::new(&cli);
         for arg in &args {
             cmd.arg(arg);
         }
         cmd.arg("setup")
+            .envs(network::child_env(&read_config().network))
+            .kill_on_drop(true)
             .output()
+            .await
             .map_err(|e| format!("Setup failed: {}", e))?
     };
 
@@ -553,6 +851,7 @@ fn run_setup(window: tauri::Window) -> Result<String, String> {
     let _ = window.emit("install_progress", serde_json::json!({
         "step": "setup",
         "status": if output.status.success() { "done" } else { "error" },
+        "task_id": task_id,
         "message": if output.status.success() {
             "VSCode extension installed.".to_string()
         } else {
@@ -567,6 +866,20 @@ fn run_setup(window: tauri::Window) -> Result<String, String> {
     }
 }
 
+#[tauri::command]
+fn list_tasks(tasks: tauri::State<'_, TaskManager>) -> Vec<tasks::TaskInfo> {
+    tasks.list()
+}
+
+#[tauri::command]
+fn cancel_task(tasks: tauri::State<'_, TaskManager>, task_id: String) -> Result<(), String> {
+    if tasks.cancel(&task_id) {
+        Ok(())
+    } else {
+        Err(format!("No running task {}", task_id))
+    }
+}
+
 #[tauri::command]
 fn save_environments(environments: Vec<String>) -> Result<(), String> {
     let mut config = read_config();
@@ -575,6 +888,17 @@ fn save_environments(environments: Vec<String>) -> Result<(), String> {
     Ok(())
 }
 
+#[tauri::command]
+fn save_agent_port(port: Option<u16>) -> Result<(), String> {
+    if port == Some(0) {
+        return Err("Port must be between 1 and 65535".to_string());
+    }
+    let mut config = read_config();
+    config.port = port;
+    save_config(&config);
+    Ok(())
+}
+
 #[tauri::command]
 fn mark_installed() -> Result<(), String> {
     ensure_config_dir();
@@ -582,7 +906,6 @@ fn mark_installed() -> Result<(), String> {
     fs::write(&marker, "installed").map_err(|e| format!("Cannot write marker: {}", e))
 }
 
-#[tauri::command]
 fn read_config() -> AppConfig {
     let path = get_config_dir().join("config.json");
     if let Ok(data) = fs::read_to_string(&path) {
@@ -592,253 +915,1322 @@ fn read_config() -> AppConfig {
     }
 }
 
+/// What the webview sees of config.json: tokens stay in the backend.
+#[derive(Serialize)]
+pub struct ConfigStatus {
+    pub email: Option<String>,
+    pub has_auth_token: bool,
+    pub needs_relogin: bool,
+    pub has_admin_token: bool,
+}
+
+#[tauri::command]
+fn get_config() -> ConfigStatus {
+    let config = read_config();
+    ConfigStatus {
+        email: config.email,
+        has_auth_token: config.auth_token.is_some_and(|t| !t.is_empty()),
+        needs_relogin: config.needs_relogin,
+        has_admin_token: config.admin_token.is_some_and(|t| !t.is_empty()),
+    }
+}
+
 fn save_config(config: &AppConfig) {
     ensure_config_dir();
     let path = get_config_dir().join("config.json");
     if let Ok(json) = serde_json::to_string_pretty(config) {
-        let _ = fs::write(path, json);
+        let _ = fs::write(&path, json);
+        // Holds the account token and, for admins, the relay's AUTH_TOKEN
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let _ = fs::set_permissions(&path, fs::Permissions::from_mode(0o600));
+        }
     }
 }
 
-#[tauri::command]
-fn register(email: String, password: String) -> Result<serde_json::Value, String> {
-    let client = reqwest::blocking::Client::new();
-    let res = client
-        .post(format!("{}/api/auth/register", RELAY_API))
-        .json(&serde_json::json!({ "email": email, "password": password }))
-        .send()
-        .map_err(|e| format!("Request failed: {}", e))?;
-
-    let status = res.status();
-    let body: serde_json::Value = res.json().map_err(|e| format!("Parse error: {}", e))?;
-
-    if !status.is_success() {
-        let error = body["error"].as_str().unwrap_or("Registration failed");
-        return Err(error.to_string());
-    }
-
-    // If we got a token directly, save config
-    if let Some(token) = body["auth_token"].as_str() {
+fn save_login(email: String, response: &AuthResponse) {
+    if let Some(token) = &response.auth_token {
         let mut config = read_config();
-        config.auth_token = Some(token.to_string());
+        config.auth_token = Some(token.clone());
         config.email = Some(email);
+        config.needs_relogin = false;
         save_config(&config);
     }
-
-    Ok(body)
 }
 
-#[tauri::command]
-fn verify_email(email: String, code: String) -> Result<serde_json::Value, String> {
-    let client = reqwest::blocking::Client::new();
-    let res = client
-        .post(format!("{}/api/auth/verify-email", RELAY_API))
-        .json(&serde_json::json!({ "email": email, "code": code }))
-        .send()
-        .map_err(|e| format!("Request failed: {}", e))?;
+/// The relay rejected our token: flag config.json, stop the agent (it
+/// can't reconnect with the stale token) and send the UI to Login.
+fn expire_session(app_handle: &tauri::AppHandle) {
+    let mut config = read_config();
+    if config.needs_relogin || config.auth_token.is_none() {
+        return;
+    }
+    log::warn!("Relay rejected the auth token, login required");
+    config.needs_relogin = true;
+    save_config(&config);
+    stop_agent_internal();
+    let _ = app_handle.emit("session_expired", serde_json::json!({ "email": config.email }));
+}
 
-    let status = res.status();
-    let body: serde_json::Value = res.json().map_err(|e| format!("Parse error: {}", e))?;
+/// Passes `result` through, expiring the session first if it was a 401. A
+/// blocked account keeps its session; the approval poller picks up the change.
+async fn check_session<T>(app_handle: &tauri::AppHandle, result: Result<T, RelayError>) -> Result<T, RelayError> {
+    match &result {
+        Err(RelayError::SessionExpired) => {
+            let app_handle = app_handle.clone();
+            let _ = blocking(move || {
+                expire_session(&app_handle);
+                Ok(())
+            })
+            .await;
+        }
+        Err(RelayError::AccountBlocked(_)) => watch_approval(app_handle),
+        _ => {}
+    }
+    result
+}
 
-    if !status.is_success() {
-        let error = body["error"].as_str().unwrap_or("Verification failed");
-        return Err(error.to_string());
+/// Starts the approval poller if the app manages one; it exits on its own
+/// when the account isn't pending.
+fn watch_approval(app_handle: &tauri::AppHandle) {
+    if let Some(watcher) = app_handle.try_state::<Arc<ApprovalWatcher>>() {
+        watcher.start(app_handle.clone());
     }
+}
 
-    // If we got a token, save config
-    if let Some(token) = body["auth_token"].as_str() {
-        let mut config = read_config();
-        config.auth_token = Some(token.to_string());
-        config.email = Some(email);
-        save_config(&config);
+#[tauri::command]
+async fn register(
+    app_handle: tauri::AppHandle,
+    relay: tauri::State<'_, RelayClient>,
+    email: String,
+    password: String,
+) -> Result<AuthResponse, RelayError> {
+    let response = relay.register(&email, &password, &device::identity()).await?;
+    // If we got a token directly, save config
+    save_login(email, &response);
+    if response.auth_token.is_some() {
+        watch_approval(&app_handle);
     }
+    Ok(response)
+}
 
-    Ok(body)
+#[tauri::command]
+async fn verify_email(
+    app_handle: tauri::AppHandle,
+    relay: tauri::State<'_, RelayClient>,
+    email: String,
+    code: String,
+) -> Result<AuthResponse, RelayError> {
+    let response = relay.verify_email(&email, &code, &device::identity()).await?;
+    save_login(email, &response);
+    if response.auth_token.is_some() {
+        watch_approval(&app_handle);
+    }
+    Ok(response)
 }
 
 #[tauri::command]
-fn login(email: String, password: String) -> Result<serde_json::Value, String> {
-    let client = reqwest::blocking::Client::new();
-    let res = client
-        .post(format!("{}/api/auth/login", RELAY_API))
-        .json(&serde_json::json!({ "email": email, "password": password }))
-        .send()
-        .map_err(|e| format!("Request failed: {}", e))?;
+async fn login(
+    app_handle: tauri::AppHandle,
+    relay: tauri::State<'_, RelayClient>,
+    email: String,
+    password: String,
+) -> Result<AuthResponse, RelayError> {
+    let response = relay.login(&email, &password, &device::identity()).await?;
+    save_login(email, &response);
+
+    if response.auth_token.is_some() {
+        restart_agent_after_login(app_handle);
+    }
+    Ok(response)
+}
+
+/// (Re)starts the agent in the background so it connects with the new token;
+/// the login reply doesn't wait for it. Progress and failures reach the UI
+/// as `agent_status` events.
+fn restart_agent_after_login(app_handle: tauri::AppHandle) {
+    tauri::async_runtime::spawn(async move {
+        let handle = app_handle.clone();
+        let restart = blocking(move || {
+            stop_agent_internal();
+            start_agent_blocking(handle, None)
+        })
+        .await;
+        if let Err(e) = restart {
+            log::warn!("Agent restart after login failed: {}", e);
+            set_last_agent_error(Some(e.clone()));
+            let _ = app_handle.emit("agent_status", serde_json::json!({
+                "status": "error",
+                "message": e
+            }));
+        }
+    });
+}
 
-    let status = res.status();
-    let body: serde_json::Value = res.json().map_err(|e| format!("Parse error: {}", e))?;
+/// Signs in through the browser instead of a password; cancellable via
+/// `cancel_task`. Stores the token like `login`.
+#[tauri::command]
+async fn login_with_browser(
+    window: tauri::Window,
+    app_handle: tauri::AppHandle,
+    relay: tauri::State<'_, RelayClient>,
+    tasks: tauri::State<'_, TaskManager>,
+) -> Result<AuthResponse, String> {
+    let relay = relay.inner().clone();
+    let device = device::identity();
+    let response = tasks
+        .run(&window, "browser_login", |_, _| {
+            let app_handle = app_handle.clone();
+            let relay = relay.clone();
+            async move {
+                device_login::authorize(&app_handle, &relay, &device)
+                    .await
+                    .map_err(String::from)
+            }
+        })
+        .await?;
+
+    // The grant only returns a token; ask the relay whose it is
+    let token = response.auth_token.clone().ok_or("Relay returned no token")?;
+    let account = relay.with_token(token).me().await?;
+    save_login(account.email, &response);
+    restart_agent_after_login(app_handle);
+    Ok(response)
+}
 
-    if !status.is_success() {
-        let error = body["error"].as_str().unwrap_or("Login failed");
-        return Err(error.to_string());
-    }
+#[tauri::command]
+async fn resend_verification_code(relay: tauri::State<'_, RelayClient>, email: String) -> Result<AuthResponse, RelayError> {
+    relay.resend_code(&email).await
+}
 
-    if let Some(token) = body["auth_token"].as_str() {
-        let mut config = read_config();
-        config.auth_token = Some(token.to_string());
-        config.email = Some(email);
-        save_config(&config);
-    }
+#[tauri::command]
+async fn request_password_reset(relay: tauri::State<'_, RelayClient>, email: String) -> Result<AuthResponse, RelayError> {
+    relay.request_password_reset(&email).await
+}
 
-    Ok(body)
+#[tauri::command]
+async fn confirm_password_reset(
+    relay: tauri::State<'_, RelayClient>,
+    email: String,
+    code: String,
+    password: String,
+) -> Result<AuthResponse, RelayError> {
+    let response = relay.confirm_password_reset(&email, &code, &password, &device::identity()).await?;
+    // The relay rotates the token on reset; keep ours in sync
+    save_login(email, &response);
+    Ok(response)
 }
 
+/// Forgets the login only; the device ID and settings outlive it.
 #[tauri::command]
 fn logout() -> Result<(), String> {
     stop_agent_internal();
-    let config_path = get_config_dir().join("config.json");
-    let _ = fs::remove_file(config_path);
+    let mut config = read_config();
+    config.auth_token = None;
+    config.email = None;
+    config.needs_relogin = false;
+    save_config(&config);
     Ok(())
 }
 
+#[derive(Serialize, Clone)]
+pub struct AgentStartResult {
+    pub ready: bool,
+    pub relay_public_url: String,
+    pub version: String,
+}
+
+#[derive(Serialize)]
+pub struct AgentStatusReport {
+    pub pid: Option<u32>,
+    pub process_alive: bool,
+    pub http_reachable: bool,
+    pub port: u16,
+    pub relay_connected: bool,
+    pub relay_url: String,
+    pub session_count: usize,
+    pub version: String,
+    pub uptime_secs: Option<u64>,
+    pub last_error: Option<String>,
+}
+
+/// Runs blocking work (local agent HTTP, child processes) off the async
+/// runtime so a slow call never stalls IPC.
+async fn blocking<T, F>(f: F) -> Result<T, String>
+where
+    F: FnOnce() -> Result<T, String> + Send + 'static,
+    T: Send + 'static,
+{
+    tauri::async_runtime::spawn_blocking(f)
+        .await
+        .map_err(|e| format!("Background task failed: {}", e))?
+}
+
+/// Last failure seen while starting the agent, surfaced by `agent_status`
+/// when the agent itself can't be asked.
+static LAST_AGENT_ERROR: std::sync::Mutex<Option<String>> = std::sync::Mutex::new(None);
+
+fn set_last_agent_error(error: Option<String>) {
+    if let Ok(mut last) = LAST_AGENT_ERROR.lock() {
+        *last = error;
+    }
+}
+
 #[tauri::command]
-fn start_agent() -> Result<(), String> {
+async fn start_agent(app_handle: tauri::AppHandle, wait: Option<bool>) -> Result<AgentStartResult, String> {
+    blocking(move || start_agent_blocking(app_handle, wait)).await
+}
+
+fn start_agent_blocking(app_handle: tauri::AppHandle, wait: Option<bool>) -> Result<AgentStartResult, String> {
+    if read_config().needs_relogin {
+        return Err("Session expired, please log in again".to_string());
+    }
+
+    // The agent reports device_id/device_name from config.json to the relay
+    device::identity();
+
     let node_config = read_node_config();
     let (cli, args) = get_cli_binary(node_config.portable);
 
+    let mut config = read_config();
+    let configured_port = config.port.unwrap_or(DEFAULT_AGENT_PORT);
+    // Our agent is already up: a second one would only die on EADDRINUSE
+    if let Ok(health) = AgentClient::new(configured_port).health() {
+        set_last_agent_error(None);
+        let _ = app_handle.emit("agent_status", serde_json::json!({
+            "status": "ready",
+            "message": "Agent is running.",
+            "relayPublicUrl": health.relay_public_url,
+            "version": health.version
+        }));
+        return Ok(AgentStartResult {
+            ready: true,
+            relay_public_url: health.relay_public_url,
+            version: health.version,
+        });
+    }
+    // If the port is taken by something other than our agent, move to a free one
+    let port = select_agent_port(configured_port)?;
+    if port != configured_port {
+        log::info!("Agent port {} is in use, using {} instead", configured_port, port);
+        config.port = Some(port);
+        save_config(&config);
+    }
+
     // Build the full command string for the agent
     let mut cli_cmd = cli.clone();
     for arg in &args {
         cli_cmd.push(' ');
         cli_cmd.push_str(arg);
     }
-    cli_cmd.push_str(" start");
+    cli_cmd.push_str(&format!(" start --port {}", port));
+
+    let stderr_log = open_agent_log()?;
+
+    if let Some(warning) = network::node_proxy_warning(&config.network, node_version(node_config.portable).as_deref()) {
+        log::warn!("{}", warning);
+        let _ = app_handle.emit("agent_status", serde_json::json!({
+            "status": "warning",
+            "message": warning
+        }));
+    }
 
     // Use CREATE_NO_WINDOW to prevent the PowerShell console from flashing.
     // This only suppresses the console window — it does NOT affect the desktop
     // session, so systray2's message pump still works correctly.
     #[cfg(target_os = "windows")]
-    {
+    let child = {
         use std::os::windows::process::CommandExt;
         const CREATE_NO_WINDOW: u32 = 0x08000000;
         Command::new("powershell.exe")
             .args(["-NonInteractive", "-Command", &cli_cmd])
             .creation_flags(CREATE_NO_WINDOW)
+            .env("HTTP_PORT", port.to_string())
+            .envs(network::child_env(&config.network))
             .stdin(std::process::Stdio::null())
             .stdout(std::process::Stdio::null())
-            .stderr(std::process::Stdio::null())
+            .stderr(stderr_log)
             .spawn()
-            .map_err(|e| format!("Failed to start agent: {}", e))?;
-    }
+            .map_err(|e| format!("Failed to start agent: {}", e))?
+    };
 
     #[cfg(not(target_os = "windows"))]
-    {
+    let child = {
         let mut cmd = Command::new(&cli);
         for arg in &args {
             cmd.arg(arg);
         }
         cmd.arg("start")
+            .arg("--port")
+            .arg(port.to_string())
+            .env("HTTP_PORT", port.to_string())
+            .envs(network::child_env(&config.network))
             .stdin(std::process::Stdio::null())
             .stdout(std::process::Stdio::null())
-            .stderr(std::process::Stdio::null())
+            .stderr(stderr_log)
             .spawn()
-            .map_err(|e| format!("Failed to start agent: {}", e))?;
-    }
+            .map_err(|e| format!("Failed to start agent: {}", e))?
+    };
 
-    Ok(())
-}
+    let _ = app_handle.emit("agent_status", serde_json::json!({
+        "status": "starting",
+        "message": "Starting agent..."
+    }));
 
-fn stop_agent_internal() {
-    let pid_path = get_config_dir().join("agent.pid");
-    if let Ok(pid_str) = fs::read_to_string(&pid_path) {
-        if let Ok(pid) = pid_str.trim().parse::<u32>() {
-            #[cfg(target_os = "windows")]
-            {
-                use std::os::windows::process::CommandExt;
-                const CREATE_NO_WINDOW: u32 = 0x08000000;
-                let _ = Command::new("taskkill")
-                    .args(["/PID", &pid.to_string(), "/F"])
-                    .creation_flags(CREATE_NO_WINDOW)
-                    .output();
-            }
-            #[cfg(not(target_os = "windows"))]
-            {
-                unsafe {
-                    libc::kill(pid as i32, libc::SIGTERM);
-                }
-            }
-        }
+    if wait.unwrap_or(false) {
+        return wait_for_agent_ready(&app_handle, child, port);
     }
-    let _ = fs::remove_file(pid_path);
-}
 
-#[tauri::command]
-fn stop_agent() -> Result<(), String> {
-    stop_agent_internal();
+    // Not waiting: report readiness through events only
+    std::thread::spawn(move || {
+        let _ = wait_for_agent_ready(&app_handle, child, port);
+    });
+
+    Ok(AgentStartResult {
+        ready: false,
+        relay_public_url: String::new(),
+        version: String::new(),
+    })
+}
+
+/// Truncates the agent's stderr log for a fresh start.
+fn open_agent_log() -> Result<fs::File, String> {
+    ensure_config_dir();
+    let path = get_config_dir().join(AGENT_LOG_FILE);
+    fs::File::create(&path).map_err(|e| format!("Cannot create {}: {}", path.display(), e))
+}
+
+/// The last AGENT_STDERR_LIMIT bytes of the agent's stderr log.
+fn read_agent_log_tail() -> String {
+    use std::io::{Seek, SeekFrom};
+
+    let Ok(mut file) = fs::File::open(get_config_dir().join(AGENT_LOG_FILE)) else {
+        return String::new();
+    };
+    let len = file.metadata().map(|m| m.len()).unwrap_or(0);
+    if file.seek(SeekFrom::Start(len.saturating_sub(AGENT_STDERR_LIMIT))).is_err() {
+        return String::new();
+    }
+    let mut buf = Vec::new();
+    let _ = file.read_to_end(&mut buf);
+    String::from_utf8_lossy(&buf).trim().to_string()
+}
+
+fn wait_for_agent_ready(
+    app_handle: &tauri::AppHandle,
+    mut child: std::process::Child,
+    port: u16,
+) -> Result<AgentStartResult, String> {
+    let client = AgentClient::new(port);
+    let deadline = std::time::Instant::now() + std::time::Duration::from_secs(AGENT_READY_TIMEOUT_SECS);
+
+    let result = loop {
+        if let Ok(health) = client.health() {
+            break Ok(AgentStartResult {
+                ready: true,
+                relay_public_url: health.relay_public_url,
+                version: health.version,
+            });
+        }
+
+        if let Ok(Some(status)) = child.try_wait() {
+            let stderr = read_agent_log_tail();
+            break Err(if stderr.is_empty() {
+                format!("Agent exited before becoming ready ({})", status)
+            } else {
+                format!("Agent exited before becoming ready ({}): {}", status, stderr)
+            });
+        }
+
+        if std::time::Instant::now() >= deadline {
+            break Err(format!(
+                "Agent did not become ready within {} seconds",
+                AGENT_READY_TIMEOUT_SECS
+            ));
+        }
+
+        std::thread::sleep(std::time::Duration::from_millis(250));
+    };
+
+    set_last_agent_error(result.as_ref().err().cloned());
+
+    let _ = match &result {
+        Ok(info) => app_handle.emit("agent_status", serde_json::json!({
+            "status": "ready",
+            "message": "Agent is running.",
+            "relayPublicUrl": info.relay_public_url,
+            "version": info.version
+        })),
+        Err(e) => app_handle.emit("agent_status", serde_json::json!({
+            "status": "error",
+            "message": e
+        })),
+    };
+
+    result
+}
+
+fn read_agent_pid() -> Option<u32> {
+    fs::read_to_string(get_config_dir().join("agent.pid"))
+        .ok()
+        .and_then(|s| s.trim().parse::<u32>().ok())
+}
+
+fn is_process_alive(pid: u32) -> bool {
+    #[cfg(target_os = "windows")]
+    {
+        use std::os::windows::process::CommandExt;
+        const CREATE_NO_WINDOW: u32 = 0x08000000;
+        Command::new("tasklist")
+            .args(["/FI", &format!("PID eq {}", pid), "/NH", "/FO", "CSV"])
+            .creation_flags(CREATE_NO_WINDOW)
+            .output()
+            .map(|o| String::from_utf8_lossy(&o.stdout).contains(&format!("\"{}\"", pid)))
+            .unwrap_or(false)
+    }
+    #[cfg(not(target_os = "windows"))]
+    {
+        // Signal 0 only checks existence; EPERM still means the process is there
+        let rc = unsafe { libc::kill(pid as i32, 0) };
+        rc == 0 || std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+    }
+}
+
+fn stop_agent_internal() {
+    let pid_path = get_config_dir().join("agent.pid");
+    if let Ok(pid_str) = fs::read_to_string(&pid_path) {
+        if let Ok(pid) = pid_str.trim().parse::<u32>() {
+            #[cfg(target_os = "windows")]
+            {
+                use std::os::windows::process::CommandExt;
+                const CREATE_NO_WINDOW: u32 = 0x08000000;
+                let _ = Command::new("taskkill")
+                    .args(["/PID", &pid.to_string(), "/F"])
+                    .creation_flags(CREATE_NO_WINDOW)
+                    .output();
+            }
+            #[cfg(not(target_os = "windows"))]
+            {
+                unsafe {
+                    libc::kill(pid as i32, libc::SIGTERM);
+                }
+            }
+        }
+    }
+    let _ = fs::remove_file(pid_path);
+}
+
+#[tauri::command]
+async fn stop_agent() -> Result<(), String> {
+    blocking(|| {
+        stop_agent_internal();
+        Ok(())
+    })
+    .await
+}
+
+#[cfg(target_os = "linux")]
+fn autostart_config_home() -> Result<PathBuf, String> {
+    let home = dirs::home_dir().ok_or("Cannot determine home directory")?;
+    Ok(autostart::config_home(&home))
+}
+
+#[cfg(target_os = "linux")]
+fn autostart_launch() -> Result<autostart::AgentLaunch, String> {
+    let node_config = read_node_config();
+    let (cli, mut args) = get_cli_binary(node_config.portable);
+    let program = resolve_on_path(&cli)
+        .ok_or_else(|| format!("Cannot find {} on PATH", cli))?
+        .to_string_lossy()
+        .to_string();
+
+    args.push("start".to_string());
+    args.push("--port".to_string());
+    args.push(get_agent_port().to_string());
+
+    let path_prefix = if node_config.portable {
+        Some(get_portable_node_dir().join("bin").to_string_lossy().to_string())
+    } else {
+        None
+    };
+
+    Ok(autostart::AgentLaunch {
+        program,
+        args,
+        path_prefix,
+    })
+}
+
+#[cfg(target_os = "linux")]
+#[tauri::command]
+fn enable_autostart(method: Option<String>) -> Result<autostart::AutostartStatus, String> {
+    let method = autostart::AutostartMethod::parse(method.as_deref().unwrap_or("xdg"))?;
+    autostart::enable(&autostart_config_home()?, method, &autostart_launch()?)
+}
+
+#[cfg(target_os = "linux")]
+#[tauri::command]
+fn disable_autostart() -> Result<(), String> {
+    autostart::disable(&autostart_config_home()?)
+}
+
+#[cfg(target_os = "linux")]
+#[tauri::command]
+fn autostart_status() -> Result<autostart::AutostartStatus, String> {
+    Ok(autostart::status(&autostart_config_home()?))
+}
+
+#[cfg(not(target_os = "linux"))]
+const AUTOSTART_UNSUPPORTED: &str = "Autostart is only supported on Linux";
+
+#[cfg(not(target_os = "linux"))]
+#[tauri::command]
+fn enable_autostart(method: Option<String>) -> Result<(), String> {
+    let _ = method;
+    Err(AUTOSTART_UNSUPPORTED.to_string())
+}
+
+#[cfg(not(target_os = "linux"))]
+#[tauri::command]
+fn disable_autostart() -> Result<(), String> {
+    Err(AUTOSTART_UNSUPPORTED.to_string())
+}
+
+#[cfg(not(target_os = "linux"))]
+#[tauri::command]
+fn autostart_status() -> Result<(), String> {
+    Err(AUTOSTART_UNSUPPORTED.to_string())
+}
+
+#[tauri::command]
+async fn check_account_status(
+    app_handle: tauri::AppHandle,
+    relay: tauri::State<'_, RelayClient>,
+) -> Result<AccountStatus, RelayError> {
+    let result = match relay.authenticated() {
+        Ok(client) => client.me().await,
+        Err(e) => Err(e),
+    };
+    check_session(&app_handle, result).await
+}
+
+/// Ensures the background approval poller is running; returns whether it is
+/// (false once the account is no longer pending).
+#[tauri::command]
+fn watch_account_status(app_handle: tauri::AppHandle, watcher: tauri::State<'_, Arc<ApprovalWatcher>>) -> bool {
+    watcher.start(app_handle);
+    watcher.is_running()
+}
+
+// ── Devices ──
+
+#[tauri::command]
+fn get_device_identity() -> device::DeviceIdentity {
+    device::identity()
+}
+
+#[tauri::command]
+async fn list_devices(
+    app_handle: tauri::AppHandle,
+    relay: tauri::State<'_, RelayClient>,
+) -> Result<Vec<Device>, RelayError> {
+    let result = match relay.authenticated() {
+        Ok(client) => client.list_devices().await,
+        Err(e) => Err(e),
+    };
+    check_session(&app_handle, result).await
+}
+
+/// Renames any of the account's devices. Renaming this one also updates
+/// the name sent on the next login and agent start.
+#[tauri::command]
+async fn rename_device(
+    app_handle: tauri::AppHandle,
+    relay: tauri::State<'_, RelayClient>,
+    device_id: String,
+    name: String,
+) -> Result<(), RelayError> {
+    let name = device::clean_name(&name).map_err(|message| RelayError::Server { status: 400, message })?;
+    let is_current = device::identity().device_id == device_id;
+    if is_current {
+        let _ = device::set_name(&name);
+    }
+    let result = match relay.authenticated() {
+        Ok(client) => client.rename_device(&device_id, &name).await,
+        Err(e) => Err(e),
+    };
+    match check_session(&app_handle, result).await {
+        // Still on the account token: the relay learns the name at next login
+        Err(RelayError::Server { status: 404, .. }) if is_current => Ok(()),
+        other => other,
+    }
+}
+
+/// Signs another device out by invalidating its token.
+#[tauri::command]
+async fn revoke_device(
+    app_handle: tauri::AppHandle,
+    relay: tauri::State<'_, RelayClient>,
+    device_id: String,
+) -> Result<(), RelayError> {
+    let result = match relay.authenticated() {
+        Ok(client) => client.revoke_device(&device_id).await,
+        Err(e) => Err(e),
+    };
+    check_session(&app_handle, result).await
+}
+
+// ── Relay administration ──
+
+/// Stores the admin token; an empty or missing one removes it.
+#[tauri::command]
+fn save_admin_token(token: Option<String>) {
+    let mut config = read_config();
+    config.admin_token = token.map(|t| t.trim().to_string()).filter(|t| !t.is_empty());
+    save_config(&config);
+}
+
+#[tauri::command]
+async fn admin_list_users(relay: tauri::State<'_, RelayClient>) -> Result<Vec<AdminUser>, RelayError> {
+    relay.admin()?.list_users().await
+}
+
+#[tauri::command]
+async fn admin_approve_user(relay: tauri::State<'_, RelayClient>, user_id: i64) -> Result<(), RelayError> {
+    relay.admin()?.approve_user(user_id).await
+}
+
+#[tauri::command]
+async fn admin_reject_user(relay: tauri::State<'_, RelayClient>, user_id: i64) -> Result<(), RelayError> {
+    relay.admin()?.reject_user(user_id).await
+}
+
+#[tauri::command]
+async fn check_agent_health() -> Result<HealthResult, String> {
+    blocking(|| {
+        Ok(HealthResult {
+            running: agent_is_healthy(get_agent_port()),
+        })
+    })
+    .await
+}
+
+#[tauri::command]
+async fn agent_status(app_handle: tauri::AppHandle) -> Result<AgentStatusReport, String> {
+    blocking(move || Ok(agent_status_report(&app_handle))).await
+}
+
+fn agent_status_report(app_handle: &tauri::AppHandle) -> AgentStatusReport {
+    let port = get_agent_port();
+    let pid = read_agent_pid();
+    let process_alive = pid.map(is_process_alive).unwrap_or(false);
+
+    let client = AgentClient::new(port);
+    let health = client.health().ok();
+    if health.as_ref().is_some_and(|h| h.auth_rejected) {
+        expire_session(app_handle);
+    }
+    let session_count = if health.is_some() {
+        client.list_sessions().map(|s| s.len()).unwrap_or(0)
+    } else {
+        0
+    };
+
+    let local_error = LAST_AGENT_ERROR.lock().ok().and_then(|e| e.clone());
+
+    match health {
+        Some(health) => AgentStatusReport {
+            pid,
+            process_alive,
+            http_reachable: true,
+            port,
+            relay_connected: health.relay_connected,
+            relay_url: health.relay_public_url,
+            session_count,
+            version: health.version,
+            uptime_secs: health.uptime,
+            last_error: health.last_error.or(local_error),
+        },
+        None => AgentStatusReport {
+            pid,
+            process_alive,
+            http_reachable: false,
+            port,
+            relay_connected: false,
+            relay_url: String::new(),
+            session_count: 0,
+            version: String::new(),
+            uptime_secs: None,
+            last_error: local_error.or_else(|| {
+                if process_alive {
+                    Some(format!("Agent process is running but not answering on port {}", port))
+                } else {
+                    None
+                }
+            }),
+        },
+    }
+}
+
+async fn send_agent_command(command: AgentCommand) -> Result<(), String> {
+    blocking(move || AgentClient::new(get_agent_port()).send_command(&command)).await
+}
+
+#[tauri::command]
+async fn list_sessions() -> Result<Vec<agent_client::AgentSession>, String> {
+    blocking(|| AgentClient::new(get_agent_port()).list_sessions()).await
+}
+
+#[tauri::command]
+async fn share_session(project_path: String, project_name: Option<String>) -> Result<agent_client::ShareResult, String> {
+    blocking(move || AgentClient::new(get_agent_port()).share_session(&project_path, project_name.as_deref())).await
+}
+
+#[tauri::command]
+async fn reshare_session(session_id: String) -> Result<(), String> {
+    blocking(move || AgentClient::new(get_agent_port()).reshare_session(&session_id)).await
+}
+
+#[tauri::command]
+async fn unshare_session(session_id: String) -> Result<(), String> {
+    blocking(move || AgentClient::new(get_agent_port()).unshare_session(&session_id)).await
+}
+
+#[tauri::command]
+async fn remove_session(session_id: String) -> Result<(), String> {
+    blocking(move || AgentClient::new(get_agent_port()).remove_session(&session_id)).await
+}
+
+#[tauri::command]
+async fn send_session_message(session_id: String, content: String) -> Result<(), String> {
+    if content.trim().is_empty() {
+        return Err("Message is empty".to_string());
+    }
+    send_agent_command(AgentCommand::UserMessage { session_id, content }).await
+}
+
+#[tauri::command]
+async fn respond_permission(session_id: String, permission_id: String, action: String) -> Result<(), String> {
+    if action != "allow" && action != "deny" {
+        return Err(format!("Invalid permission action: {}", action));
+    }
+    send_agent_command(AgentCommand::PermissionResponse {
+        session_id,
+        permission_id,
+        action,
+    })
+    .await
+}
+
+#[tauri::command]
+async fn dismiss_question(session_id: String) -> Result<(), String> {
+    send_agent_command(AgentCommand::DismissQuestion { session_id }).await
+}
+
+#[tauri::command]
+async fn stop_session(session_id: String) -> Result<(), String> {
+    send_agent_command(AgentCommand::StopMessage { session_id }).await
+}
+
+#[tauri::command]
+async fn set_auto_accept(session_id: String, auto_accept: bool) -> Result<(), String> {
+    send_agent_command(AgentCommand::SetAutoAccept {
+        session_id,
+        auto_accept,
+    })
+    .await
+}
+
+#[tauri::command]
+fn list_auto_accept_policies() -> Vec<auto_accept::AutoAcceptPolicy> {
+    read_config().auto_accept_policies
+}
+
+#[tauri::command]
+fn save_auto_accept_policy(policy: auto_accept::AutoAcceptPolicy) -> Result<(), String> {
+    auto_accept::validate(&policy)?;
+    let mut config = read_config();
+    config.auto_accept_policies.retain(|p| p.project_path != policy.project_path);
+    config.auto_accept_policies.push(policy);
+    save_config(&config);
     Ok(())
 }
 
+#[tauri::command]
+fn delete_auto_accept_policy(project_path: String) -> Result<(), String> {
+    let mut config = read_config();
+    config.auto_accept_policies.retain(|p| p.project_path != project_path);
+    save_config(&config);
+    Ok(())
+}
+
+/// Re-applies the configured policies to running sessions (all of them, or
+/// just `session_id`), e.g. after editing a policy.
+#[tauri::command]
+async fn apply_auto_accept_policies(
+    enforcer: tauri::State<'_, Arc<auto_accept::PolicyEnforcer>>,
+    session_id: Option<String>,
+) -> Result<usize, String> {
+    let enforcer = enforcer.inner().clone();
+    blocking(move || {
+        let sessions = AgentClient::new(get_agent_port()).list_sessions()?;
+        let mut applied = 0;
+        for session in sessions {
+            if session_id.as_ref().is_some_and(|id| id != &session.id) {
+                continue;
+            }
+            if enforcer.apply(&session)?.is_some() {
+                applied += 1;
+            }
+        }
+        Ok(applied)
+    })
+    .await
+}
+
+#[tauri::command]
+fn read_auto_accept_audit(limit: Option<usize>) -> Vec<serde_json::Value> {
+    auto_accept::read_audit(limit.unwrap_or(200))
+}
+
 #[derive(Serialize)]
-pub struct AccountStatus {
-    pub status: String,
-    pub email: String,
-    pub email_verified: bool,
+pub struct ShareProjectResult {
+    pub session_id: String,
+    pub project_name: String,
+    pub project_path: String,
+    pub session_token: String,
+    pub relay_public_url: String,
+    pub already_shared: bool,
+}
+
+fn now_millis() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+fn remember_recent_project(config: &mut AppConfig, path: &str, name: &str) {
+    config.recent_projects.retain(|p| p.path != path);
+    config.recent_projects.insert(
+        0,
+        RecentProject {
+            path: path.to_string(),
+            name: name.to_string(),
+            last_shared: now_millis(),
+        },
+    );
+    config.recent_projects.truncate(MAX_RECENT_PROJECTS);
 }
 
 #[tauri::command]
-fn check_account_status() -> Result<AccountStatus, String> {
-    let config = read_config();
-    let token = config.auth_token.unwrap_or_default();
-    if token.is_empty() {
-        return Err("Not logged in".to_string());
+async fn pick_project_folder(app_handle: tauri::AppHandle) -> Option<String> {
+    use tauri_plugin_dialog::DialogExt;
+
+    app_handle
+        .dialog()
+        .file()
+        .set_title("Share project to mobile")
+        .blocking_pick_folder()
+        .and_then(|p| p.into_path().ok())
+        .map(|p| p.to_string_lossy().to_string())
+}
+
+#[tauri::command]
+async fn share_project(project_path: String) -> Result<ShareProjectResult, String> {
+    // Keep the path as the user chose it (no canonicalize) so it matches what
+    // the VSCode extension sends for the same folder.
+    let trimmed = project_path.trim();
+    let path = PathBuf::from(trimmed.trim_end_matches(['/', '\\']));
+    if trimmed.is_empty() || !path.is_absolute() {
+        return Err("Project path must be an absolute path".to_string());
+    }
+    if !path.is_dir() {
+        return Err(format!("Not a directory: {}", path.display()));
     }
 
-    let client = reqwest::blocking::Client::builder()
-        .timeout(std::time::Duration::from_secs(5))
-        .build()
-        .unwrap_or_else(|_| reqwest::blocking::Client::new());
+    let path_str = path.to_string_lossy().to_string();
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_else(|| path_str.clone());
+
+    let result = {
+        let path_str = path_str.clone();
+        blocking(move || AgentClient::new(get_agent_port()).share_session(&path_str, Some(&name))).await?
+    };
+
+    let mut config = read_config();
+    remember_recent_project(&mut config, &path_str, &result.session.project_name);
+    save_config(&config);
+
+    Ok(ShareProjectResult {
+        session_id: result.session.id,
+        project_name: result.session.project_name,
+        project_path: result.session.project_path,
+        session_token: result.session.session_token,
+        relay_public_url: result.relay_public_url,
+        already_shared: result.already_shared,
+    })
+}
+
+#[tauri::command]
+fn list_recent_projects() -> Vec<RecentProject> {
+    read_config().recent_projects
+}
+
+#[derive(Serialize)]
+pub struct PairingQrCode {
+    pub url: String,
+    pub png: String,
+    pub svg: String,
+    pub pairing_code: Option<String>,
+    pub expires_at: Option<String>,
+}
+
+#[tauri::command]
+async fn pairing_qr_code(
+    app_handle: tauri::AppHandle,
+    relay: tauri::State<'_, RelayClient>,
+    with_pairing_code: Option<bool>,
+) -> Result<PairingQrCode, String> {
+    // Prefer the URL the running agent reports, it's what the phone must open
+    let relay_url = blocking(|| AgentClient::new(get_agent_port()).health())
+        .await
+        .ok()
+        .map(|h| h.relay_public_url)
+        .filter(|u| !u.is_empty())
+        .unwrap_or_else(|| RELAY_API.to_string());
+
+    let (url, pairing_code, expires_at) = if with_pairing_code.unwrap_or(false) {
+        let result = match relay.authenticated() {
+            Ok(client) => client.pairing_code().await,
+            Err(e) => Err(e),
+        };
+        let pairing = check_session(&app_handle, result).await?;
+        let (code, expires_at) = (pairing.code, pairing.expires_at);
+        (
+            format!("{}/?pair={}", relay_url.trim_end_matches('/'), code),
+            Some(code),
+            expires_at,
+        )
+    } else {
+        (relay_url, None, None)
+    };
+
+    let png = qr::render_png(&url, QR_MODULE_PX)?;
+    let svg = qr::render_svg(&url, QR_SVG_MIN_SIZE)?;
+
+    Ok(PairingQrCode {
+        png: qr::png_data_url(&png),
+        svg,
+        url,
+        pairing_code,
+        expires_at,
+    })
+}
+
+#[tauri::command]
+fn get_session_states(events: tauri::State<'_, Arc<SessionEvents>>) -> Vec<session_events::SessionSnapshot> {
+    events.snapshots()
+}
+
+#[tauri::command]
+fn get_notification_settings() -> notifications::NotificationSettings {
+    read_config().notifications
+}
+
+#[tauri::command]
+fn save_notification_settings(settings: notifications::NotificationSettings) -> Result<(), String> {
+    notifications::validate(&settings)?;
+    let mut config = read_config();
+    config.notifications = settings;
+    save_config(&config);
+    Ok(())
+}
+
+#[tauri::command]
+fn get_network_settings() -> network::NetworkSettings {
+    read_config().network
+}
+
+/// Applies to relay requests right away; a running agent picks the new
+/// proxy and CA up when it is restarted.
+#[tauri::command]
+fn save_network_settings(relay: tauri::State<'_, RelayClient>, settings: network::NetworkSettings) -> Result<(), String> {
+    network::validate(&settings)?;
+    let mut config = read_config();
+    config.network = settings.clone();
+    save_config(&config);
+    relay.reconfigure(&settings);
+    Ok(())
+}
 
-    let res = client
-        .get(format!("{}/api/auth/me", RELAY_API))
-        .header("Authorization", format!("Bearer {}", token))
-        .send()
-        .map_err(|e| format!("Request failed: {}", e))?;
+#[tauri::command]
+fn get_npm_registry() -> npmrc::NpmRegistryStatus {
+    read_config().npm_registry.status()
+}
 
-    let status_code = res.status();
-    let body: serde_json::Value = res.json().map_err(|e| format!("Parse error: {}", e))?;
+/// `auth_token`: None keeps the stored token, an empty string removes it.
+#[tauri::command]
+fn save_npm_registry(registry_url: Option<String>, auth_token: Option<String>) -> Result<npmrc::NpmRegistryStatus, String> {
+    let mut config = read_config();
+    let mut settings = npmrc::NpmRegistrySettings {
+        registry_url: registry_url.map(|u| u.trim().to_string()).filter(|u| !u.is_empty()),
+        auth_token: config.npm_registry.auth_token.clone(),
+    };
+    if let Some(token) = auth_token {
+        settings.auth_token = Some(token.trim().to_string()).filter(|t| !t.is_empty());
+    }
+    npmrc::validate(&settings)?;
+    config.npm_registry = settings;
+    save_config(&config);
+    Ok(config.npm_registry.status())
+}
 
-    if !status_code.is_success() {
-        let error = body["error"].as_str().unwrap_or("Unknown error");
-        return Err(error.to_string());
+#[tauri::command]
+fn set_project_muted(project_path: String, muted: bool) -> Result<(), String> {
+    let mut config = read_config();
+    let muted_projects = &mut config.notifications.muted_projects;
+    muted_projects.retain(|p| p != &project_path);
+    if muted {
+        muted_projects.push(project_path);
     }
+    save_config(&config);
+    Ok(())
+}
+
+/// Full history from the relay when logged in, otherwise (or if the relay
+/// doesn't know the session) what the app has seen: the in-memory buffer
+/// for live sessions, the history database for removed ones.
+fn session_messages(
+    events: &SessionEvents,
+    history: Option<&history::HistoryStore>,
+    session_id: &str,
+    source: &str,
+) -> Result<Vec<serde_json::Value>, String> {
+    let from_local = || {
+        let messages = events.transcript(session_id);
+        match history {
+            Some(store) if messages.is_empty() => store.session_messages(session_id),
+            _ => Ok(messages),
+        }
+    };
+
+    let from_relay = || {
+        let config = read_config();
+        let token = config.auth_token.unwrap_or_default();
+        if token.is_empty() {
+            return Err("Not logged in".to_string());
+        }
+        let ws_url = RELAY_API.replacen("https://", "wss://", 1).replacen("http://", "ws://", 1);
+        transcript::fetch_relay_history(&config.network, &ws_url, &token, session_id)
+    };
+
+    match source {
+        "relay" => from_relay(),
+        "local" => from_local(),
+        _ => match from_relay() {
+            Ok(messages) if !messages.is_empty() => Ok(messages),
+            Ok(_) => from_local(),
+            Err(e) => {
+                log::warn!("Relay history unavailable, using local transcript: {}", e);
+                from_local()
+            }
+        },
+    }
+}
+
+#[tauri::command]
+async fn export_session_transcript(
+    app_handle: tauri::AppHandle,
+    events: tauri::State<'_, Arc<SessionEvents>>,
+    session_id: String,
+    format: String,
+    path: Option<String>,
+    source: Option<String>,
+) -> Result<Option<String>, String> {
+    use tauri_plugin_dialog::DialogExt;
+
+    let format = transcript::ExportFormat::parse(&format)?;
+    let events = events.inner().clone();
+    let history = app_handle
+        .try_state::<Arc<history::HistoryStore>>()
+        .map(|h| h.inner().clone());
+    let id = session_id.clone();
+    let (messages, session) = blocking(move || {
+        let messages = session_messages(&events, history.as_deref(), &id, source.as_deref().unwrap_or("auto"))?;
+        let session = events.session(&id).map(|s| s.session).or_else(|| {
+            AgentClient::new(get_agent_port())
+                .list_sessions()
+                .ok()
+                .and_then(|list| list.into_iter().find(|s| s.id == id))
+        });
+        Ok((messages, session))
+    })
+    .await?;
+    if messages.is_empty() {
+        return Err("No messages recorded for this session".to_string());
+    }
+
+    let meta = transcript::TranscriptMeta {
+        project_name: session
+            .as_ref()
+            .map(|s| s.project_name.clone())
+            .unwrap_or_else(|| session_id.clone()),
+        project_path: session.map(|s| s.project_path).unwrap_or_default(),
+        session_id,
+    };
+
+    let path = match path {
+        Some(p) => PathBuf::from(p),
+        None => {
+            let file_name = format!("{}-transcript.{}", meta.project_name, format.extension());
+            let picked = app_handle
+                .dialog()
+                .file()
+                .set_title("Export transcript")
+                .set_file_name(file_name)
+                .add_filter("Transcript", &[format.extension()])
+                .blocking_save_file()
+                .and_then(|p| p.into_path().ok());
+            match picked {
+                Some(p) => p,
+                // Dialog cancelled
+                None => return Ok(None),
+            }
+        }
+    };
+
+    let content = transcript::render(format, &meta, &messages);
+    fs::write(&path, content).map_err(|e| format!("Failed to write transcript: {}", e))?;
+    Ok(Some(path.to_string_lossy().to_string()))
+}
+
+fn history_store(app_handle: &tauri::AppHandle) -> Result<Arc<history::HistoryStore>, String> {
+    app_handle
+        .try_state::<Arc<history::HistoryStore>>()
+        .map(|s| s.inner().clone())
+        .ok_or_else(|| "Session history is unavailable".to_string())
+}
+
+#[tauri::command]
+async fn search_history(app_handle: tauri::AppHandle, query: history::HistoryQuery) -> Result<Vec<history::HistoryEntry>, String> {
+    let store = history_store(&app_handle)?;
+    blocking(move || store.search(&query)).await
+}
+
+#[tauri::command]
+async fn list_history_sessions(
+    app_handle: tauri::AppHandle,
+    project_path: Option<String>,
+) -> Result<Vec<history::HistorySession>, String> {
+    let store = history_store(&app_handle)?;
+    blocking(move || store.sessions(project_path.as_deref())).await
+}
+
+#[tauri::command]
+fn get_history_retention() -> history::HistoryRetention {
+    read_config().history_retention
+}
 
-    Ok(AccountStatus {
-        status: body["status"].as_str().unwrap_or("unknown").to_string(),
-        email: body["email"].as_str().unwrap_or("").to_string(),
-        email_verified: body["email_verified"].as_bool().unwrap_or(false),
+/// Saves the limits and prunes right away; returns the number of entries removed.
+#[tauri::command]
+async fn save_history_retention(app_handle: tauri::AppHandle, retention: history::HistoryRetention) -> Result<u64, String> {
+    if retention.max_age_days == Some(0) || retention.max_entries == Some(0) {
+        return Err("Retention limits must be at least 1".to_string());
+    }
+    let store = history_store(&app_handle)?;
+    blocking(move || {
+        let mut config = read_config();
+        config.history_retention = retention.clone();
+        save_config(&config);
+        store.set_retention(retention)
     })
+    .await
 }
 
 #[tauri::command]
-fn check_agent_health() -> HealthResult {
-    let client = reqwest::blocking::Client::builder()
-        .timeout(std::time::Duration::from_secs(2))
-        .build()
-        .unwrap_or_else(|_| reqwest::blocking::Client::new());
+async fn clear_history(app_handle: tauri::AppHandle, session_id: Option<String>) -> Result<u64, String> {
+    let store = history_store(&app_handle)?;
+    blocking(move || store.clear(session_id.as_deref())).await
+}
 
-    let running = client
-        .get("http://127.0.0.1:9680/health")
-        .send()
-        .map(|r| r.status().is_success())
-        .unwrap_or(false);
+fn usage_store(app_handle: &tauri::AppHandle) -> Result<Arc<usage::UsageStore>, String> {
+    app_handle
+        .try_state::<Arc<usage::UsageStore>>()
+        .map(|s| s.inner().clone())
+        .ok_or_else(|| "Usage tracking is unavailable".to_string())
+}
 
-    HealthResult { running }
+#[tauri::command]
+async fn usage_summary(
+    app_handle: tauri::AppHandle,
+    group_by: usage::UsageGroup,
+    query: Option<usage::UsageQuery>,
+) -> Result<Vec<usage::UsageAggregate>, String> {
+    let query = query.unwrap_or_default();
+    usage::validate(&query)?;
+    let store = usage_store(&app_handle)?;
+    blocking(move || store.aggregate(group_by, &query)).await
 }
 
 #[tauri::command]
-fn check_relay_health() -> HealthResult {
-    let client = reqwest::blocking::Client::builder()
-        .timeout(std::time::Duration::from_secs(5))
-        .build()
-        .unwrap_or_else(|_| reqwest::blocking::Client::new());
+async fn usage_time_series(
+    app_handle: tauri::AppHandle,
+    query: Option<usage::UsageQuery>,
+) -> Result<Vec<usage::UsageAggregate>, String> {
+    let query = query.unwrap_or_default();
+    usage::validate(&query)?;
+    let store = usage_store(&app_handle)?;
+    blocking(move || store.daily_series(&query)).await
+}
 
-    let running = client
-        .get(format!("{}/health", RELAY_API))
-        .send()
-        .map(|r| r.status().is_success())
-        .unwrap_or(false);
+#[tauri::command]
+async fn export_usage_csv(
+    app_handle: tauri::AppHandle,
+    group_by: usage::UsageGroup,
+    query: Option<usage::UsageQuery>,
+    path: Option<String>,
+) -> Result<Option<String>, String> {
+    use tauri_plugin_dialog::DialogExt;
+
+    let query = query.unwrap_or_default();
+    usage::validate(&query)?;
+    let rows = match group_by {
+        usage::UsageGroup::Day => usage_store(&app_handle)?.daily_series(&query)?,
+        _ => usage_store(&app_handle)?.aggregate(group_by, &query)?,
+    };
 
-    HealthResult { running }
+    let path = match path {
+        Some(p) => PathBuf::from(p),
+        None => {
+            let picked = app_handle
+                .dialog()
+                .file()
+                .set_title("Export usage")
+                .set_file_name("claude-usage.csv")
+                .add_filter("CSV", &["csv"])
+                .blocking_save_file()
+                .and_then(|p| p.into_path().ok());
+            match picked {
+                Some(p) => p,
+                // Dialog cancelled
+                None => return Ok(None),
+            }
+        }
+    };
+
+    fs::write(&path, usage::to_csv(&rows)).map_err(|e| format!("Failed to write CSV: {}", e))?;
+    Ok(Some(path.to_string_lossy().to_string()))
+}
+
+#[tauri::command]
+async fn check_relay_health(relay: tauri::State<'_, RelayClient>) -> Result<HealthResult, String> {
+    Ok(HealthResult {
+        running: relay.is_healthy().await,
+    })
 }
 
 // ── App entry ──
@@ -854,25 +2246,123 @@ pub fn run() {
                         .build(),
                 )?;
             }
+
+            let events = Arc::new(SessionEvents::default());
+            let notifier = notifications::Notifier::default();
+            events.add_handler(Box::new(move |app, event| notifier.handle(app, event)));
+            let enforcer = Arc::new(auto_accept::PolicyEnforcer::default());
+            let policy_handler = enforcer.clone();
+            events.add_handler(Box::new(move |app, event| policy_handler.handle(app, event)));
+            app.manage(enforcer);
+            match history::HistoryStore::open(read_config().history_retention) {
+                Ok(store) => {
+                    let store = Arc::new(store);
+                    let history_handler = store.clone();
+                    events.add_handler(Box::new(move |app, event| history_handler.handle(app, event)));
+                    app.manage(store);
+                }
+                Err(e) => log::warn!("Session history disabled: {}", e),
+            }
+            match usage::UsageStore::open() {
+                Ok(store) => {
+                    let store = Arc::new(store);
+                    let usage_handler = store.clone();
+                    events.add_handler(Box::new(move |app, event| usage_handler.handle(app, event)));
+                    app.manage(store);
+                }
+                Err(e) => log::warn!("Usage tracking disabled: {}", e),
+            }
+            // Managed before the listener starts so its handlers can reach it
+            app.manage(events.clone());
+            session_events::spawn_listener(app.handle().clone(), events, get_agent_port);
+
+            let watcher = Arc::new(ApprovalWatcher::default());
+            if read_config().auth_token.is_some() {
+                watcher.start(app.handle().clone());
+            }
+            app.manage(watcher);
+
             Ok(())
         })
+        .manage(RelayClient::new())
+        .manage(TaskManager::default())
+        .plugin(tauri_plugin_dialog::init())
+        .plugin(tauri_plugin_shell::init())
+        .plugin(tauri_plugin_notification::init())
         .invoke_handler(tauri::generate_handler![
             check_install_state,
             check_node,
             download_portable_node,
             install_npm_package,
+            get_install_manifest,
             run_setup,
+            list_tasks,
+            cancel_task,
             save_environments,
+            save_agent_port,
             mark_installed,
-            read_config,
+            get_config,
             register,
             verify_email,
             login,
+            resend_verification_code,
+            request_password_reset,
+            confirm_password_reset,
             logout,
             start_agent,
             stop_agent,
+            enable_autostart,
+            disable_autostart,
+            autostart_status,
             check_account_status,
+            watch_account_status,
+            login_with_browser,
+            get_device_identity,
+            list_devices,
+            rename_device,
+            revoke_device,
+            save_admin_token,
+            admin_list_users,
+            admin_approve_user,
+            admin_reject_user,
             check_agent_health,
+            agent_status,
+            list_sessions,
+            share_session,
+            reshare_session,
+            unshare_session,
+            remove_session,
+            send_session_message,
+            respond_permission,
+            dismiss_question,
+            stop_session,
+            set_auto_accept,
+            list_auto_accept_policies,
+            save_auto_accept_policy,
+            delete_auto_accept_policy,
+            apply_auto_accept_policies,
+            read_auto_accept_audit,
+            pick_project_folder,
+            share_project,
+            list_recent_projects,
+            pairing_qr_code,
+            get_session_states,
+            get_notification_settings,
+            save_notification_settings,
+            get_network_settings,
+            save_network_settings,
+            get_npm_registry,
+            save_npm_registry,
+            set_project_muted,
+            export_session_transcript,
+            search_history,
+            list_history_sessions,
+            get_history_retention,
+            save_history_retention,
+            clear_history,
+            usage_summary,
+            usage_time_series,
+            export_usage_csv,
             check_relay_health,
         ])
         .run(tauri::generate_context!())
@@ -0,0 +1,317 @@
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::time::Duration;
+
+// ── Proxy and custom CA settings for outbound traffic ──
+
+/// The agent listens on loopback, which must never go through the proxy
+const ALWAYS_NO_PROXY: [&str; 3] = ["localhost", "127.0.0.1", "::1"];
+const PROXY_TIMEOUT: Duration = Duration::from_secs(15);
+/// Upper bound on a CONNECT response head
+const PROXY_RESPONSE_LIMIT: usize = 8 * 1024;
+/// First Node.js 22 release whose built-in http(s) honours NODE_USE_ENV_PROXY
+const NODE_ENV_PROXY_SINCE: (u32, u32) = (22, 21);
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct NetworkSettings {
+    /// e.g. "http://proxy.corp:3128"; used for both HTTP and HTTPS
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy_url: Option<String>,
+    /// Hosts or domains that bypass the proxy, as in NO_PROXY
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub no_proxy: Vec<String>,
+    /// PEM file with extra root certificates to trust
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ca_bundle: Option<String>,
+}
+
+impl NetworkSettings {
+    fn proxy_url(&self) -> Option<&str> {
+        self.proxy_url.as_deref().map(str::trim).filter(|u| !u.is_empty())
+    }
+
+    fn ca_bundle(&self) -> Option<&str> {
+        self.ca_bundle.as_deref().map(str::trim).filter(|p| !p.is_empty())
+    }
+
+    fn no_proxy_list(&self) -> String {
+        let mut hosts: Vec<&str> = ALWAYS_NO_PROXY.to_vec();
+        hosts.extend(self.no_proxy.iter().map(|h| h.trim()).filter(|h| !h.is_empty()));
+        hosts.join(",")
+    }
+}
+
+fn read_certificates(path: &str) -> Result<Vec<reqwest::Certificate>, String> {
+    let pem = std::fs::read(path).map_err(|e| format!("Cannot read CA bundle {}: {}", path, e))?;
+    let certificates =
+        reqwest::Certificate::from_pem_bundle(&pem).map_err(|e| format!("Invalid CA bundle {}: {}", path, e))?;
+    if certificates.is_empty() {
+        return Err(format!("No certificates found in {}", path));
+    }
+    Ok(certificates)
+}
+
+fn proxy(settings: &NetworkSettings) -> Result<Option<reqwest::Proxy>, String> {
+    let Some(url) = settings.proxy_url() else {
+        return Ok(None);
+    };
+    let proxy = reqwest::Proxy::all(url).map_err(|e| format!("Invalid proxy URL {}: {}", url, e))?;
+    Ok(Some(proxy.no_proxy(reqwest::NoProxy::from_string(&settings.no_proxy_list()))))
+}
+
+pub fn validate(settings: &NetworkSettings) -> Result<(), String> {
+    proxy(settings)?;
+    if let Some(path) = settings.ca_bundle() {
+        read_certificates(path)?;
+    }
+    Ok(())
+}
+
+/// A client builder with the configured proxy and extra root certificates.
+/// Invalid settings are logged and skipped rather than breaking every
+/// request; `validate` rejects them when they are saved.
+pub fn client_builder(settings: &NetworkSettings) -> reqwest::ClientBuilder {
+    let mut builder = reqwest::Client::builder();
+    match proxy(settings) {
+        Ok(Some(proxy)) => builder = builder.proxy(proxy),
+        Ok(None) => {}
+        Err(e) => log::warn!("{}", e),
+    }
+    if let Some(path) = settings.ca_bundle() {
+        match read_certificates(path) {
+            Ok(certificates) => {
+                for certificate in certificates {
+                    builder = builder.add_root_certificate(certificate);
+                }
+            }
+            Err(e) => log::warn!("{}", e),
+        }
+    }
+    builder
+}
+
+/// Environment for spawned npm and agent processes, so their own downloads
+/// and relay connection use the same proxy and CA.
+pub fn child_env(settings: &NetworkSettings) -> Vec<(&'static str, String)> {
+    let mut env = Vec::new();
+    if let Some(url) = settings.proxy_url() {
+        let no_proxy = settings.no_proxy_list();
+        for key in ["HTTPS_PROXY", "HTTP_PROXY", "https_proxy", "http_proxy", "npm_config_proxy", "npm_config_https_proxy"] {
+            env.push((key, url.to_string()));
+        }
+        for key in ["NO_PROXY", "no_proxy", "npm_config_noproxy"] {
+            env.push((key, no_proxy.clone()));
+        }
+        // Lets Node's built-in http(s) honour the variables above; older
+        // Node ignores it, see `node_proxy_warning`
+        env.push(("NODE_USE_ENV_PROXY", "1".to_string()));
+    }
+    if let Some(path) = settings.ca_bundle() {
+        env.push(("npm_config_cafile", path.to_string()));
+        env.push(("NODE_EXTRA_CA_CERTS", path.to_string()));
+    }
+    env
+}
+
+/// Whether Node.js `version` (as printed by `node --version`, e.g.
+/// "v22.14.0") honours NODE_USE_ENV_PROXY.
+fn node_supports_env_proxy(version: &str) -> Option<bool> {
+    let mut parts = version.trim().trim_start_matches('v').split('.');
+    let major: u32 = parts.next()?.parse().ok()?;
+    let minor: u32 = parts.next()?.parse().ok()?;
+    // 23 is odd-numbered and never got it; 24 had it from the start
+    Some(major >= 24 || (major == NODE_ENV_PROXY_SINCE.0 && minor >= NODE_ENV_PROXY_SINCE.1))
+}
+
+/// Why the agent on Node.js `node_version` won't use the configured proxy,
+/// or None if it will (or there is no proxy, or the version is unknown).
+pub fn node_proxy_warning(settings: &NetworkSettings, node_version: Option<&str>) -> Option<String> {
+    settings.proxy_url()?;
+    let version = node_version?;
+    if node_supports_env_proxy(version)? {
+        return None;
+    }
+    Some(format!(
+        "Node.js {} ignores the proxy settings; install Node.js {}.{} or newer, or use the bundled runtime",
+        version, NODE_ENV_PROXY_SINCE.0, NODE_ENV_PROXY_SINCE.1
+    ))
+}
+
+// ── WebSocket connections (tungstenite knows nothing about proxies) ──
+
+pub type WebSocket = tungstenite::WebSocket<tungstenite::stream::MaybeTlsStream<TcpStream>>;
+
+/// `host` without the brackets URLs put around IPv6 addresses.
+fn bare_host(host: &str) -> &str {
+    host.trim_start_matches('[').trim_end_matches(']')
+}
+
+/// Whether `host` matches the NO_PROXY list: exact names, domain suffixes
+/// (`example.com` and `.example.com` both cover `a.example.com`) or `*`.
+fn bypasses_proxy(settings: &NetworkSettings, host: &str) -> bool {
+    let host = bare_host(host).to_ascii_lowercase();
+    settings.no_proxy_list().split(',').any(|entry| {
+        let entry = entry.trim().to_ascii_lowercase();
+        if entry == "*" {
+            return true;
+        }
+        let domain = entry.trim_start_matches("*.").trim_start_matches('.');
+        !domain.is_empty() && (host == domain || host.ends_with(&format!(".{}", domain)))
+    })
+}
+
+/// Opens a tunnel to `host:port` through an HTTP proxy with CONNECT.
+fn connect_through_proxy(proxy_url: &str, host: &str, port: u16) -> Result<TcpStream, String> {
+    let proxy = reqwest::Url::parse(proxy_url).map_err(|e| format!("Invalid proxy URL {}: {}", proxy_url, e))?;
+    if proxy.scheme() != "http" {
+        return Err(format!("WebSocket connections need an http:// proxy: {}", proxy_url));
+    }
+    let proxy_host = proxy.host_str().ok_or_else(|| format!("Invalid proxy URL: {}", proxy_url))?;
+    let proxy_port = proxy.port_or_known_default().unwrap_or(80);
+
+    let mut stream = TcpStream::connect((bare_host(proxy_host), proxy_port))
+        .map_err(|e| format!("Proxy {} not reachable: {}", proxy_url, e))?;
+    let _ = stream.set_read_timeout(Some(PROXY_TIMEOUT));
+
+    let target = format!("{}:{}", host, port);
+    let mut request = format!("CONNECT {0} HTTP/1.1\r\nHost: {0}\r\n", target);
+    if !proxy.username().is_empty() {
+        let decode = |s: &str| percent_encoding::percent_decode_str(s).decode_utf8_lossy().to_string();
+        let credentials = format!("{}:{}", decode(proxy.username()), decode(proxy.password().unwrap_or_default()));
+        request.push_str(&format!(
+            "Proxy-Authorization: Basic {}\r\n",
+            base64::engine::general_purpose::STANDARD.encode(credentials)
+        ));
+    }
+    request.push_str("\r\n");
+    stream
+        .write_all(request.as_bytes())
+        .map_err(|e| format!("Cannot write to proxy: {}", e))?;
+
+    // Byte by byte, so nothing after the response head is consumed
+    let mut head = Vec::new();
+    let mut byte = [0u8; 1];
+    while !head.ends_with(b"\r\n\r\n") {
+        if head.len() >= PROXY_RESPONSE_LIMIT {
+            return Err("Proxy response too long".to_string());
+        }
+        match stream.read(&mut byte) {
+            Ok(0) => return Err("Proxy closed the connection".to_string()),
+            Ok(_) => head.push(byte[0]),
+            Err(e) => return Err(format!("No answer from proxy: {}", e)),
+        }
+    }
+    let head = String::from_utf8_lossy(&head);
+    let status = head.lines().next().unwrap_or_default();
+    if status.split_whitespace().nth(1) != Some("200") {
+        return Err(format!("Proxy refused to connect to {}: {}", target, status));
+    }
+
+    let _ = stream.set_read_timeout(None);
+    Ok(stream)
+}
+
+/// Trusts the configured CA bundle on top of the system roots.
+fn tls_connector(path: &str) -> Result<native_tls::TlsConnector, String> {
+    let pem = std::fs::read_to_string(path).map_err(|e| format!("Cannot read CA bundle {}: {}", path, e))?;
+    let mut builder = native_tls::TlsConnector::builder();
+    for block in pem.split_inclusive("-----END CERTIFICATE-----") {
+        if !block.contains("-----BEGIN CERTIFICATE-----") {
+            continue;
+        }
+        let certificate = native_tls::Certificate::from_pem(block.as_bytes())
+            .map_err(|e| format!("Invalid CA bundle {}: {}", path, e))?;
+        builder.add_root_certificate(certificate);
+    }
+    builder.build().map_err(|e| format!("Cannot set up TLS: {}", e))
+}
+
+/// Opens a WebSocket through the configured proxy and with the extra root
+/// certificates, which plain `tungstenite::connect` would ignore. Like
+/// `client_builder`, an unreadable CA bundle is logged and skipped.
+pub fn connect_websocket(settings: &NetworkSettings, url: &reqwest::Url) -> Result<WebSocket, String> {
+    let host = url.host_str().ok_or_else(|| format!("No host in {}", url))?;
+    let port = url.port_or_known_default().ok_or_else(|| format!("No port for {}", url))?;
+
+    let stream = match settings.proxy_url().filter(|_| !bypasses_proxy(settings, host)) {
+        Some(proxy) => connect_through_proxy(proxy, host, port)?,
+        None => TcpStream::connect((bare_host(host), port)).map_err(|e| format!("{} not reachable: {}", host, e))?,
+    };
+
+    let connector = match settings.ca_bundle().map(tls_connector) {
+        Some(Ok(connector)) => Some(tungstenite::Connector::NativeTls(connector)),
+        Some(Err(e)) => {
+            log::warn!("{}", e);
+            None
+        }
+        None => None,
+    };
+
+    let (socket, _) = tungstenite::client_tls_with_config(url.as_str(), stream, None, connector)
+        .map_err(|e| format!("WebSocket handshake with {} failed: {}", host, e))?;
+    Ok(socket)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(no_proxy: &[&str]) -> NetworkSettings {
+        NetworkSettings {
+            proxy_url: Some("http://proxy.corp:3128".to_string()),
+            no_proxy: no_proxy.iter().map(|h| h.to_string()).collect(),
+            ca_bundle: None,
+        }
+    }
+
+    #[test]
+    fn loopback_always_bypasses_the_proxy() {
+        let settings = settings(&[]);
+        assert!(bypasses_proxy(&settings, "localhost"));
+        assert!(bypasses_proxy(&settings, "127.0.0.1"));
+        assert!(bypasses_proxy(&settings, "[::1]"));
+        assert!(!bypasses_proxy(&settings, "relay.example.com"));
+    }
+
+    #[test]
+    fn no_proxy_matches_domain_suffixes() {
+        let settings = settings(&["example.com", ".corp.net", " Internal "]);
+        assert!(bypasses_proxy(&settings, "example.com"));
+        assert!(bypasses_proxy(&settings, "relay.example.com"));
+        assert!(!bypasses_proxy(&settings, "notexample.com"));
+        assert!(bypasses_proxy(&settings, "a.b.corp.net"));
+        assert!(bypasses_proxy(&settings, "INTERNAL"));
+    }
+
+    #[test]
+    fn wildcard_bypasses_everything() {
+        assert!(bypasses_proxy(&settings(&["*"]), "relay.example.com"));
+    }
+
+    #[test]
+    fn old_node_versions_get_a_proxy_warning() {
+        let proxied = settings(&[]);
+        assert!(node_proxy_warning(&proxied, Some("v22.14.0")).is_some());
+        assert!(node_proxy_warning(&proxied, Some("v20.18.1")).is_some());
+        assert!(node_proxy_warning(&proxied, Some("v23.11.0")).is_some());
+        assert!(node_proxy_warning(&proxied, Some("v22.21.0")).is_none());
+        assert!(node_proxy_warning(&proxied, Some("v24.1.0\n")).is_none());
+        // Nothing to say without a proxy or a readable version
+        assert!(node_proxy_warning(&NetworkSettings::default(), Some("v18.0.0")).is_none());
+        assert!(node_proxy_warning(&proxied, None).is_none());
+        assert!(node_proxy_warning(&proxied, Some("unknown")).is_none());
+    }
+
+    #[test]
+    fn child_env_comes_from_the_given_settings() {
+        assert!(child_env(&NetworkSettings::default()).is_empty());
+
+        let env = child_env(&settings(&["corp.net"]));
+        let get = |key: &str| env.iter().find(|(k, _)| *k == key).map(|(_, v)| v.as_str());
+        assert_eq!(get("HTTPS_PROXY"), Some("http://proxy.corp:3128"));
+        assert_eq!(get("NO_PROXY"), Some("localhost,127.0.0.1,::1,corp.net"));
+        assert_eq!(get("NODE_USE_ENV_PROXY"), Some("1"));
+    }
+}
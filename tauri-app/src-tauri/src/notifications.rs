@@ -0,0 +1,256 @@
+use chrono::Timelike;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use tauri::Manager;
+use tauri_plugin_notification::NotificationExt;
+
+use crate::session_events::{AgentEvent, SessionEvents};
+
+// ── Desktop notifications for session events ──
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct NotificationTypeSettings {
+    pub enabled: bool,
+    #[serde(default = "default_true")]
+    pub sound: bool,
+}
+
+impl Default for NotificationTypeSettings {
+    fn default() -> Self {
+        NotificationTypeSettings {
+            enabled: true,
+            sound: true,
+        }
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Local-time window ("HH:MM") during which no notifications are shown.
+/// `start` after `end` wraps past midnight, e.g. 22:00–07:00.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct QuietHours {
+    pub start: String,
+    pub end: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct NotificationSettings {
+    #[serde(default)]
+    pub input_required: NotificationTypeSettings,
+    #[serde(default)]
+    pub permission_required: NotificationTypeSettings,
+    #[serde(default)]
+    pub task_finished: NotificationTypeSettings,
+    /// Project paths whose sessions never notify
+    #[serde(default)]
+    pub muted_projects: Vec<String>,
+    #[serde(default)]
+    pub quiet_hours: Option<QuietHours>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum NotificationKind {
+    InputRequired,
+    PermissionRequired,
+    TaskFinished,
+}
+
+impl NotificationSettings {
+    fn for_kind(&self, kind: NotificationKind) -> &NotificationTypeSettings {
+        match kind {
+            NotificationKind::InputRequired => &self.input_required,
+            NotificationKind::PermissionRequired => &self.permission_required,
+            NotificationKind::TaskFinished => &self.task_finished,
+        }
+    }
+}
+
+pub fn parse_hhmm(value: &str) -> Result<u32, String> {
+    let (h, m) = value
+        .split_once(':')
+        .ok_or_else(|| format!("Invalid time (expected HH:MM): {}", value))?;
+    let h: u32 = h.trim().parse().map_err(|_| format!("Invalid hour: {}", value))?;
+    let m: u32 = m.trim().parse().map_err(|_| format!("Invalid minute: {}", value))?;
+    if h > 23 || m > 59 {
+        return Err(format!("Invalid time: {}", value));
+    }
+    Ok(h * 60 + m)
+}
+
+/// `minute_of_day` is 0..1440 in local time.
+pub fn in_quiet_hours(quiet: &QuietHours, minute_of_day: u32) -> bool {
+    let (Ok(start), Ok(end)) = (parse_hhmm(&quiet.start), parse_hhmm(&quiet.end)) else {
+        return false;
+    };
+    if start <= end {
+        minute_of_day >= start && minute_of_day < end
+    } else {
+        minute_of_day >= start || minute_of_day < end
+    }
+}
+
+pub fn validate(settings: &NotificationSettings) -> Result<(), String> {
+    if let Some(quiet) = &settings.quiet_hours {
+        parse_hhmm(&quiet.start)?;
+        parse_hhmm(&quiet.end)?;
+    }
+    Ok(())
+}
+
+fn prompt_text(prompt: &serde_json::Value) -> String {
+    match prompt {
+        serde_json::Value::String(s) if !s.is_empty() => s.clone(),
+        _ => "Claude needs your input".to_string(),
+    }
+}
+
+/// Turns agent events into desktop notifications, applying the user's
+/// per-type, per-project and quiet-hours settings.
+#[derive(Default)]
+pub struct Notifier {
+    last_status: Mutex<HashMap<String, String>>,
+}
+
+impl Notifier {
+    /// Returns the notification for `event`, if it warrants one.
+    fn classify(&self, event: &AgentEvent) -> Option<(NotificationKind, String, String)> {
+        match event {
+            AgentEvent::InputRequired { session_id, prompt } => Some((
+                NotificationKind::InputRequired,
+                session_id.clone(),
+                prompt_text(prompt),
+            )),
+            AgentEvent::ClaudeOutput { session_id, message } if message["type"] == "permission_request" => {
+                let tool = message["toolName"].as_str().unwrap_or("A tool");
+                let summary = message["summary"].as_str().unwrap_or("Approve?");
+                Some((
+                    NotificationKind::PermissionRequired,
+                    session_id.clone(),
+                    format!("{}: {}", tool, summary),
+                ))
+            }
+            AgentEvent::SessionStatus { session_id, status } => {
+                let previous = self
+                    .last_status
+                    .lock()
+                    .ok()
+                    .and_then(|mut s| s.insert(session_id.clone(), status.clone()));
+                if status == "idle" && previous.as_deref() == Some("processing") {
+                    Some((
+                        NotificationKind::TaskFinished,
+                        session_id.clone(),
+                        "Claude finished the task".to_string(),
+                    ))
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        }
+    }
+
+    pub fn handle(&self, app: &tauri::AppHandle, event: &AgentEvent) {
+        let Some((kind, session_id, body)) = self.classify(event) else {
+            return;
+        };
+
+        let settings = crate::read_config().notifications;
+        let type_settings = settings.for_kind(kind);
+        if !type_settings.enabled {
+            return;
+        }
+
+        if let Some(quiet) = &settings.quiet_hours {
+            let now = chrono::Local::now();
+            if in_quiet_hours(quiet, now.hour() * 60 + now.minute()) {
+                return;
+            }
+        }
+
+        let session = app
+            .try_state::<Arc<SessionEvents>>()
+            .and_then(|events| events.session(&session_id));
+        let (project_name, project_path) = session
+            .map(|s| (s.session.project_name, s.session.project_path))
+            .unwrap_or_default();
+        if settings.muted_projects.iter().any(|p| p == &project_path) {
+            return;
+        }
+
+        let title = match kind {
+            NotificationKind::InputRequired => "Input required",
+            NotificationKind::PermissionRequired => "Permission required",
+            NotificationKind::TaskFinished => "Task finished",
+        };
+        let title = if project_name.is_empty() {
+            title.to_string()
+        } else {
+            format!("{} — {}", title, project_name)
+        };
+
+        let mut builder = app.notification().builder().title(title).body(body);
+        if type_settings.sound {
+            builder = builder.sound("default");
+        }
+        if let Err(e) = builder.show() {
+            log::warn!("Cannot show notification: {}", e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quiet(start: &str, end: &str) -> QuietHours {
+        QuietHours {
+            start: start.to_string(),
+            end: end.to_string(),
+        }
+    }
+
+    #[test]
+    fn parses_hhmm() {
+        assert_eq!(parse_hhmm("00:00"), Ok(0));
+        assert_eq!(parse_hhmm("7:05"), Ok(7 * 60 + 5));
+        assert_eq!(parse_hhmm(" 23 : 59 "), Ok(23 * 60 + 59));
+    }
+
+    #[test]
+    fn rejects_invalid_hhmm() {
+        for value in ["", "12", "24:00", "12:60", "ab:cd", "-1:00", "12:30:00"] {
+            assert!(parse_hhmm(value).is_err(), "{} should be rejected", value);
+        }
+    }
+
+    #[test]
+    fn quiet_hours_within_a_day() {
+        let quiet = quiet("13:00", "14:30");
+        assert!(!in_quiet_hours(&quiet, 12 * 60 + 59));
+        assert!(in_quiet_hours(&quiet, 13 * 60));
+        assert!(in_quiet_hours(&quiet, 14 * 60 + 29));
+        assert!(!in_quiet_hours(&quiet, 14 * 60 + 30));
+    }
+
+    #[test]
+    fn quiet_hours_wrap_past_midnight() {
+        let quiet = quiet("22:00", "07:00");
+        assert!(!in_quiet_hours(&quiet, 21 * 60 + 59));
+        assert!(in_quiet_hours(&quiet, 22 * 60));
+        assert!(in_quiet_hours(&quiet, 23 * 60 + 59));
+        assert!(in_quiet_hours(&quiet, 0));
+        assert!(in_quiet_hours(&quiet, 6 * 60 + 59));
+        assert!(!in_quiet_hours(&quiet, 7 * 60));
+        assert!(!in_quiet_hours(&quiet, 12 * 60));
+    }
+
+    #[test]
+    fn empty_or_invalid_quiet_hours_never_apply() {
+        assert!(!in_quiet_hours(&quiet("08:00", "08:00"), 8 * 60));
+        assert!(!in_quiet_hours(&quiet("25:00", "07:00"), 0));
+    }
+}
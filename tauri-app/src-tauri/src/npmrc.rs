@@ -0,0 +1,187 @@
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+// ── App-scoped .npmrc for a private registry ──
+
+const NPMRC_FILE: &str = "npmrc";
+const REDACTED: &str = "***";
+/// npmrc keys whose values are credentials
+const SECRET_KEYS: [&str; 3] = ["_authToken=", "_auth=", "_password="];
+
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct NpmRegistrySettings {
+    /// e.g. "https://npm.corp.example/repository/npm/"; None means the public registry
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub registry_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auth_token: Option<String>,
+}
+
+/// What the frontend sees: the token itself never leaves the backend.
+#[derive(Serialize)]
+pub struct NpmRegistryStatus {
+    pub registry_url: Option<String>,
+    pub has_auth_token: bool,
+}
+
+impl NpmRegistrySettings {
+    pub fn status(&self) -> NpmRegistryStatus {
+        NpmRegistryStatus {
+            registry_url: self.registry_url.clone(),
+            has_auth_token: self.auth_token.as_deref().is_some_and(|t| !t.is_empty()),
+        }
+    }
+}
+
+pub fn validate(settings: &NpmRegistrySettings) -> Result<(), String> {
+    if let Some(url) = &settings.registry_url {
+        let parsed = reqwest::Url::parse(url).map_err(|e| format!("Invalid registry URL {}: {}", url, e))?;
+        if !matches!(parsed.scheme(), "http" | "https") || parsed.host_str().is_none() {
+            return Err(format!("Registry URL must be http(s): {}", url));
+        }
+    }
+    if settings.auth_token.is_some() && settings.registry_url.is_none() {
+        return Err("An auth token needs a registry URL".to_string());
+    }
+    Ok(())
+}
+
+/// "//host[:port]/path/", the prefix npm scopes registry credentials to.
+fn nerf_dart(url: &str) -> Option<String> {
+    let parsed = reqwest::Url::parse(url).ok()?;
+    let host = parsed.host_str()?;
+    let port = parsed.port().map(|p| format!(":{}", p)).unwrap_or_default();
+    let path = parsed.path().trim_end_matches('/');
+    Some(format!("//{}{}{}/", host, port, path))
+}
+
+fn render(settings: &NpmRegistrySettings) -> String {
+    let mut out = String::from("; Written by Remote Clauding; edits are overwritten\n");
+    if let Some(url) = &settings.registry_url {
+        out.push_str(&format!("registry={}\n", url));
+        if let (Some(token), Some(prefix)) = (settings.auth_token.as_deref().filter(|t| !t.is_empty()), nerf_dart(url)) {
+            out.push_str(&format!("{}:_authToken={}\n", prefix, token));
+        }
+    }
+    out
+}
+
+/// Writes the app's .npmrc and returns the `--userconfig` arguments every
+/// npm invocation gets, so the user's own ~/.npmrc doesn't interfere.
+pub fn userconfig_args(settings: &NpmRegistrySettings) -> Result<Vec<String>, String> {
+    crate::ensure_config_dir();
+    let path = crate::get_config_dir().join(NPMRC_FILE);
+    write(&path, settings)?;
+    Ok(vec!["--userconfig".to_string(), path.to_string_lossy().to_string()])
+}
+
+fn write(path: &Path, settings: &NpmRegistrySettings) -> Result<(), String> {
+    std::fs::write(path, render(settings)).map_err(|e| format!("Cannot write {}: {}", path.display(), e))?;
+    // The file may hold a registry token
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let _ = std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o600));
+    }
+    Ok(())
+}
+
+/// Masks the configured token and any npmrc credential values in npm output
+/// before it is logged or shown.
+pub fn redact(settings: &NpmRegistrySettings, text: &str) -> String {
+    let mut out = text.to_string();
+    if let Some(token) = settings.auth_token.as_deref().filter(|t| !t.is_empty()) {
+        out = out.replace(token, REDACTED);
+    }
+    for key in SECRET_KEYS {
+        let mut from = 0;
+        while let Some(pos) = out[from..].find(key) {
+            let mut start = from + pos + key.len();
+            // A quoted value runs to its closing quote
+            let quote = out[start..].chars().next().filter(|c| *c == '"' || *c == '\'');
+            if quote.is_some() {
+                start += 1;
+            }
+            let end = out[start..]
+                .find(|c: char| match quote {
+                    Some(q) => c == q,
+                    None => c.is_whitespace() || c == '"' || c == '\'',
+                })
+                .map_or(out.len(), |i| start + i);
+            out.replace_range(start..end, REDACTED);
+            from = start + REDACTED.len();
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(url: &str, token: Option<&str>) -> NpmRegistrySettings {
+        NpmRegistrySettings {
+            registry_url: Some(url.to_string()),
+            auth_token: token.map(|t| t.to_string()),
+        }
+    }
+
+    #[test]
+    fn renders_registry_and_scoped_token() {
+        let npmrc = render(&settings("https://npm.corp.example:8443/repository/npm/", Some("s3cret")));
+        assert!(npmrc.starts_with(';'));
+        assert!(npmrc.contains("registry=https://npm.corp.example:8443/repository/npm/\n"));
+        assert!(npmrc.contains("//npm.corp.example:8443/repository/npm/:_authToken=s3cret\n"));
+    }
+
+    #[test]
+    fn renders_nothing_but_the_header_without_a_registry() {
+        let npmrc = render(&NpmRegistrySettings::default());
+        assert_eq!(npmrc.lines().count(), 1);
+        assert!(!render(&settings("https://npm.corp.example/", Some(""))).contains("_authToken"));
+    }
+
+    #[test]
+    fn nerf_dart_keeps_host_port_and_path() {
+        assert_eq!(nerf_dart("https://npm.corp.example/").as_deref(), Some("//npm.corp.example/"));
+        assert_eq!(nerf_dart("https://npm.corp.example/a/b").as_deref(), Some("//npm.corp.example/a/b/"));
+        assert_eq!(nerf_dart("http://10.0.0.5:4873/").as_deref(), Some("//10.0.0.5:4873/"));
+        assert!(nerf_dart("not a url").is_none());
+    }
+
+    #[test]
+    fn redact_hides_the_token_and_credential_values() {
+        let settings = settings("https://npm.corp.example/", Some("s3cret"));
+        let output = "GET https://npm.corp.example/x?token=s3cret\n//other/:_authToken=abc123 _password=\"pw\"";
+        let redacted = redact(&settings, output);
+        assert!(!redacted.contains("s3cret"));
+        assert!(!redacted.contains("abc123"));
+        assert!(redacted.contains("_password=\"***\""));
+        assert!(redacted.contains(":_authToken=***"));
+        assert_eq!(redact(&NpmRegistrySettings::default(), "plain output"), "plain output");
+    }
+
+    #[test]
+    fn validates_settings() {
+        assert!(validate(&settings("https://npm.corp.example/", Some("t"))).is_ok());
+        assert!(validate(&settings("ftp://npm.corp.example/", None)).is_err());
+        assert!(validate(&settings("npm.corp.example", None)).is_err());
+        let token_only = NpmRegistrySettings {
+            registry_url: None,
+            auth_token: Some("t".to_string()),
+        };
+        assert!(validate(&token_only).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn npmrc_is_readable_by_the_owner_only() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let path = std::env::temp_dir().join(format!("rc-npmrc-{}", std::process::id()));
+        write(&path, &settings("https://npm.corp.example/", Some("s3cret"))).unwrap();
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(mode & 0o777, 0o600);
+    }
+}
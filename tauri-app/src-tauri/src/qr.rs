@@ -0,0 +1,124 @@
+use base64::Engine;
+use qrcode::{Color, EcLevel, QrCode};
+
+// ── QR codes for pairing the mobile PWA ──
+
+/// Blank modules around the code, as required by the QR spec.
+const QUIET_ZONE: u32 = 4;
+
+fn encode(data: &str) -> Result<QrCode, String> {
+    QrCode::with_error_correction_level(data.as_bytes(), EcLevel::M)
+        .map_err(|e| format!("Cannot encode QR code: {}", e))
+}
+
+/// Renders `data` as a grayscale PNG, `module_px` pixels per module.
+pub fn render_png(data: &str, module_px: u32) -> Result<Vec<u8>, String> {
+    let code = encode(data)?;
+    let modules = code.width() as u32;
+    let colors = code.to_colors();
+    let module_px = module_px.max(1);
+    let size = (modules + QUIET_ZONE * 2) * module_px;
+
+    let mut pixels = vec![255u8; (size * size) as usize];
+    for (i, color) in colors.iter().enumerate() {
+        if *color != Color::Dark {
+            continue;
+        }
+        let mx = i as u32 % modules + QUIET_ZONE;
+        let my = i as u32 / modules + QUIET_ZONE;
+        for y in my * module_px..(my + 1) * module_px {
+            let row = (y * size) as usize;
+            let start = row + (mx * module_px) as usize;
+            pixels[start..start + module_px as usize].fill(0);
+        }
+    }
+
+    let mut out = Vec::new();
+    {
+        let mut encoder = png::Encoder::new(&mut out, size, size);
+        encoder.set_color(png::ColorType::Grayscale);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder
+            .write_header()
+            .map_err(|e| format!("PNG encode error: {}", e))?;
+        writer
+            .write_image_data(&pixels)
+            .map_err(|e| format!("PNG encode error: {}", e))?;
+    }
+    Ok(out)
+}
+
+pub fn render_svg(data: &str, min_size: u32) -> Result<String, String> {
+    let code = encode(data)?;
+    Ok(code
+        .render::<qrcode::render::svg::Color>()
+        .min_dimensions(min_size, min_size)
+        .quiet_zone(true)
+        .build())
+}
+
+pub fn png_data_url(png: &[u8]) -> String {
+    format!(
+        "data:image/png;base64,{}",
+        base64::engine::general_purpose::STANDARD.encode(png)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const URL: &str = "https://relay.example.com/?pair=abc123";
+
+    fn decode_png(data: &[u8]) -> (png::OutputInfo, Vec<u8>) {
+        let mut reader = png::Decoder::new(data).read_info().unwrap();
+        let mut pixels = vec![0; reader.output_buffer_size()];
+        let info = reader.next_frame(&mut pixels).unwrap();
+        pixels.truncate(info.buffer_size());
+        (info, pixels)
+    }
+
+    #[test]
+    fn png_has_quiet_zone_and_finder_pattern() {
+        let modules = encode(URL).unwrap().width() as u32;
+        let (info, pixels) = decode_png(&render_png(URL, 3).unwrap());
+
+        assert_eq!(info.color_type, png::ColorType::Grayscale);
+        assert_eq!(info.width, (modules + QUIET_ZONE * 2) * 3);
+        assert_eq!(info.height, info.width);
+
+        let at = |mx: u32, my: u32| pixels[(my * 3 * info.width + mx * 3) as usize];
+        // Quiet zone is white, the top-left finder pattern starts dark
+        assert_eq!(at(0, 0), 255);
+        assert_eq!(at(QUIET_ZONE - 1, QUIET_ZONE), 255);
+        assert_eq!(at(QUIET_ZONE, QUIET_ZONE), 0);
+        // Finder pattern: dark ring, light ring, dark centre
+        assert_eq!(at(QUIET_ZONE + 1, QUIET_ZONE + 1), 255);
+        assert_eq!(at(QUIET_ZONE + 3, QUIET_ZONE + 3), 0);
+    }
+
+    #[test]
+    fn png_module_size_is_at_least_one() {
+        let modules = encode(URL).unwrap().width() as u32;
+        let (info, _) = decode_png(&render_png(URL, 0).unwrap());
+        assert_eq!(info.width, modules + QUIET_ZONE * 2);
+    }
+
+    #[test]
+    fn svg_respects_min_size() {
+        let svg = render_svg(URL, 256).unwrap();
+        assert!(svg.contains("<svg"));
+        let width: u32 = svg
+            .split("width=\"")
+            .nth(1)
+            .and_then(|rest| rest.split('"').next())
+            .and_then(|w| w.parse().ok())
+            .unwrap();
+        assert!(width >= 256);
+    }
+
+    #[test]
+    fn data_url_is_base64_png() {
+        assert_eq!(png_data_url(&[0x89, b'P', b'N', b'G']), "data:image/png;base64,iVBORw==");
+    }
+}
@@ -0,0 +1,744 @@
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use crate::device::DeviceIdentity;
+use crate::network::NetworkSettings;
+
+// ── HTTP client for the relay server's REST API ──
+
+const REQUEST_TIMEOUT_SECS: u64 = 10;
+const CONNECT_TIMEOUT_SECS: u64 = 5;
+
+/// Failure talking to the relay. Serialized to the frontend as
+/// `{ code, message, status }` so pages can branch on `code` instead of
+/// matching message text.
+#[derive(Debug)]
+pub enum RelayError {
+    /// No token in config.json
+    NotLoggedIn,
+    /// The relay answered 401 to our token; the user must log in again
+    SessionExpired,
+    /// The token is valid but the account is pending or rejected
+    AccountBlocked(String),
+    /// No admin token configured, or the relay refused it
+    NotAdmin,
+    /// Connection, TLS or timeout failure
+    Network(String),
+    /// Non-2xx response without a JSON `error` field (e.g. a proxy error page)
+    HttpStatus(u16),
+    /// Non-2xx response with the relay's `error` message
+    Server { status: u16, message: String },
+    /// 2xx response whose body doesn't match the expected shape
+    Decode(String),
+}
+
+impl RelayError {
+    pub fn code(&self) -> &'static str {
+        match self {
+            RelayError::NotLoggedIn => "not_logged_in",
+            RelayError::SessionExpired => "session_expired",
+            RelayError::AccountBlocked(_) => "account_blocked",
+            RelayError::NotAdmin => "not_admin",
+            RelayError::Network(_) => "network",
+            RelayError::HttpStatus(_) => "http_status",
+            RelayError::Server { .. } => "server",
+            RelayError::Decode(_) => "decode",
+        }
+    }
+
+    pub fn status(&self) -> Option<u16> {
+        match self {
+            RelayError::HttpStatus(status) | RelayError::Server { status, .. } => Some(*status),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for RelayError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RelayError::NotLoggedIn => write!(f, "Not logged in"),
+            RelayError::SessionExpired => write!(f, "Session expired, please log in again"),
+            RelayError::AccountBlocked(reason) => write!(f, "{}", reason),
+            RelayError::NotAdmin => write!(f, "Admin access required"),
+            RelayError::Network(e) => write!(f, "Request failed: {}", e),
+            RelayError::HttpStatus(status) => write!(f, "Relay returned HTTP {}", status),
+            RelayError::Server { message, .. } => write!(f, "{}", message),
+            RelayError::Decode(e) => write!(f, "Unexpected relay response: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for RelayError {}
+
+impl Serialize for RelayError {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+        let mut s = serializer.serialize_struct("RelayError", 3)?;
+        s.serialize_field("code", self.code())?;
+        s.serialize_field("message", &self.to_string())?;
+        s.serialize_field("status", &self.status())?;
+        s.end()
+    }
+}
+
+impl From<RelayError> for String {
+    fn from(e: RelayError) -> Self {
+        e.to_string()
+    }
+}
+
+// ── Request and response bodies ──
+
+// Requests that hand out a token carry the device, so the relay issues a
+// per-device one
+
+#[derive(Serialize)]
+pub struct Credentials<'a> {
+    pub email: &'a str,
+    pub password: &'a str,
+    #[serde(flatten)]
+    pub device: &'a DeviceIdentity,
+}
+
+#[derive(Serialize)]
+pub struct VerifyEmailRequest<'a> {
+    pub email: &'a str,
+    pub code: &'a str,
+    #[serde(flatten)]
+    pub device: &'a DeviceIdentity,
+}
+
+#[derive(Serialize)]
+pub struct EmailRequest<'a> {
+    pub email: &'a str,
+}
+
+#[derive(Serialize)]
+pub struct PasswordResetConfirm<'a> {
+    pub email: &'a str,
+    pub code: &'a str,
+    pub password: &'a str,
+    #[serde(flatten)]
+    pub device: &'a DeviceIdentity,
+}
+
+#[derive(Serialize)]
+struct DeviceTokenRequest<'a> {
+    device_code: &'a str,
+}
+
+#[derive(Serialize)]
+struct RenameDevice<'a> {
+    name: &'a str,
+}
+
+/// Reply of the auth endpoints; which fields are set depends on the endpoint
+/// and on the relay's verification/moderation settings.
+#[derive(Serialize, Deserialize, Clone, Default)]
+pub struct AuthResponse {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auth_token: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct AccountStatus {
+    #[serde(default = "unknown_status")]
+    pub status: String,
+    #[serde(default)]
+    pub email: String,
+    #[serde(default)]
+    pub email_verified: bool,
+    /// Set when authenticated with a per-device token
+    #[serde(default)]
+    pub device_id: Option<String>,
+}
+
+fn unknown_status() -> String {
+    "unknown".to_string()
+}
+
+#[derive(Deserialize)]
+pub struct PairingCode {
+    pub code: String,
+    #[serde(default)]
+    pub expires_at: Option<String>,
+}
+
+/// A relay account as seen by admins.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct AdminUser {
+    pub id: i64,
+    pub email: String,
+    pub status: String,
+    /// SQLite hands this out as 0/1
+    #[serde(default, deserialize_with = "bool_or_int")]
+    pub email_verified: bool,
+    #[serde(default)]
+    pub created_at: Option<String>,
+}
+
+fn bool_or_int<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<bool, D::Error> {
+    Ok(match serde_json::Value::deserialize(deserializer)? {
+        serde_json::Value::Bool(b) => b,
+        serde_json::Value::Number(n) => n.as_i64().unwrap_or(0) != 0,
+        _ => false,
+    })
+}
+
+/// Reply of `/api/auth/device-code`: the user confirms `user_code` at
+/// `verification_uri` while the app polls with `device_code`.
+#[derive(Deserialize, Clone)]
+pub struct DeviceCode {
+    pub device_code: String,
+    pub user_code: String,
+    pub verification_uri: String,
+    #[serde(default)]
+    pub verification_uri_complete: Option<String>,
+    /// Seconds
+    pub expires_in: u64,
+    /// Minimum seconds between polls
+    pub interval: u64,
+}
+
+/// One of the account's machines, as registered at login.
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Device {
+    pub device_id: String,
+    pub name: String,
+    #[serde(default)]
+    pub created_at: Option<String>,
+    #[serde(default)]
+    pub last_seen_at: Option<String>,
+    /// The device making the request
+    #[serde(default)]
+    pub current: bool,
+}
+
+#[derive(Deserialize)]
+struct DevicesResponse {
+    devices: Vec<Device>,
+}
+
+#[derive(Deserialize)]
+struct UsersResponse {
+    users: Vec<AdminUser>,
+}
+
+#[derive(Deserialize)]
+struct ErrorBody {
+    error: Option<String>,
+    code: Option<String>,
+}
+
+// ── Client ──
+
+/// Shared relay client, held in Tauri state. Cloning is cheap (the
+/// underlying connection pool is reference counted), and clones share the
+/// HTTP client so `reconfigure` reaches all of them.
+#[derive(Clone)]
+pub struct RelayClient {
+    http: Arc<RwLock<reqwest::Client>>,
+    base_url: String,
+    token: Option<String>,
+}
+
+impl RelayClient {
+    pub fn new() -> Self {
+        Self::with_base_url(crate::RELAY_API, &crate::read_config().network)
+    }
+
+    pub fn with_base_url(base_url: &str, network: &NetworkSettings) -> Self {
+        RelayClient {
+            http: Arc::new(RwLock::new(build_http_client(network))),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            token: None,
+        }
+    }
+
+    /// Rebuilds the HTTP client after the proxy or CA settings changed.
+    pub fn reconfigure(&self, network: &NetworkSettings) {
+        if let Ok(mut http) = self.http.write() {
+            *http = build_http_client(network);
+        }
+    }
+
+    /// Copy of this client authenticated with the token from config.json.
+    pub fn authenticated(&self) -> Result<Self, RelayError> {
+        let config = crate::read_config();
+        if config.needs_relogin {
+            return Err(RelayError::SessionExpired);
+        }
+        let token = config.auth_token.unwrap_or_default();
+        if token.is_empty() {
+            return Err(RelayError::NotLoggedIn);
+        }
+        Ok(self.with_token(token))
+    }
+
+    /// Copy of this client authenticated with the admin token from config.json.
+    pub fn admin(&self) -> Result<Self, RelayError> {
+        match crate::read_config().admin_token {
+            Some(token) if !token.is_empty() => Ok(self.with_token(token)),
+            _ => Err(RelayError::NotAdmin),
+        }
+    }
+
+    pub fn with_token(&self, token: String) -> Self {
+        RelayClient {
+            token: Some(token),
+            ..self.clone()
+        }
+    }
+
+    fn request(&self, method: reqwest::Method, path: &str) -> reqwest::RequestBuilder {
+        let http = self.http.read().map(|h| h.clone()).unwrap_or_else(|p| p.into_inner().clone());
+        let req = http.request(method, format!("{}{}", self.base_url, path));
+        match &self.token {
+            Some(token) => req.bearer_auth(token),
+            None => req,
+        }
+    }
+
+    async fn send<T: DeserializeOwned>(&self, req: reqwest::RequestBuilder) -> Result<T, RelayError> {
+        let res = req.send().await.map_err(|e| RelayError::Network(e.to_string()))?;
+        let status = res.status();
+        let text = res.text().await.map_err(|e| RelayError::Network(e.to_string()))?;
+
+        // Without a token a 401 is just bad credentials (e.g. login)
+        if status == reqwest::StatusCode::UNAUTHORIZED && self.token.is_some() {
+            return Err(RelayError::SessionExpired);
+        }
+        if !status.is_success() {
+            return Err(match serde_json::from_str::<ErrorBody>(&text) {
+                Ok(ErrorBody { error, code: Some(code) }) if code == "account_blocked" => {
+                    RelayError::AccountBlocked(error.unwrap_or_else(|| "Account not approved".to_string()))
+                }
+                Ok(ErrorBody { error: Some(message), .. }) => RelayError::Server {
+                    status: status.as_u16(),
+                    message,
+                },
+                _ => RelayError::HttpStatus(status.as_u16()),
+            });
+        }
+
+        serde_json::from_str(&text).map_err(|e| RelayError::Decode(e.to_string()))
+    }
+
+    async fn post<B: Serialize, T: DeserializeOwned>(&self, path: &str, body: &B) -> Result<T, RelayError> {
+        self.send(self.request(reqwest::Method::POST, path).json(body)).await
+    }
+
+    async fn get<T: DeserializeOwned>(&self, path: &str) -> Result<T, RelayError> {
+        self.send(self.request(reqwest::Method::GET, path)).await
+    }
+
+    pub async fn register(&self, email: &str, password: &str, device: &DeviceIdentity) -> Result<AuthResponse, RelayError> {
+        self.post("/api/auth/register", &Credentials { email, password, device }).await
+    }
+
+    pub async fn verify_email(&self, email: &str, code: &str, device: &DeviceIdentity) -> Result<AuthResponse, RelayError> {
+        self.post("/api/auth/verify-email", &VerifyEmailRequest { email, code, device }).await
+    }
+
+    pub async fn resend_code(&self, email: &str) -> Result<AuthResponse, RelayError> {
+        self.post("/api/auth/resend-code", &EmailRequest { email }).await
+    }
+
+    pub async fn login(&self, email: &str, password: &str, device: &DeviceIdentity) -> Result<AuthResponse, RelayError> {
+        self.post("/api/auth/login", &Credentials { email, password, device }).await
+    }
+
+    pub async fn request_device_code(&self, device: &DeviceIdentity) -> Result<DeviceCode, RelayError> {
+        self.post("/api/auth/device-code", device).await
+    }
+
+    /// Fails with `Server { message: "authorization_pending" | "slow_down" | ... }`
+    /// until the user has approved the code.
+    pub async fn poll_device_token(&self, device_code: &str) -> Result<AuthResponse, RelayError> {
+        self.post("/api/auth/device-token", &DeviceTokenRequest { device_code }).await
+    }
+
+    pub async fn request_password_reset(&self, email: &str) -> Result<AuthResponse, RelayError> {
+        self.post("/api/auth/password-reset/request", &EmailRequest { email }).await
+    }
+
+    pub async fn confirm_password_reset(
+        &self,
+        email: &str,
+        code: &str,
+        password: &str,
+        device: &DeviceIdentity,
+    ) -> Result<AuthResponse, RelayError> {
+        let body = PasswordResetConfirm {
+            email,
+            code,
+            password,
+            device,
+        };
+        self.post("/api/auth/password-reset/confirm", &body).await
+    }
+
+    pub async fn me(&self) -> Result<AccountStatus, RelayError> {
+        self.get("/api/auth/me").await
+    }
+
+    pub async fn list_devices(&self) -> Result<Vec<Device>, RelayError> {
+        let response: DevicesResponse = self.get("/api/devices").await?;
+        Ok(response.devices)
+    }
+
+    pub async fn rename_device(&self, device_id: &str, name: &str) -> Result<(), RelayError> {
+        let path = format!("/api/devices/{}", encode_path_segment(device_id));
+        let req = self.request(reqwest::Method::PATCH, &path).json(&RenameDevice { name });
+        self.send::<serde_json::Value>(req).await?;
+        Ok(())
+    }
+
+    /// Invalidates the device's token; the relay refuses the current device.
+    pub async fn revoke_device(&self, device_id: &str) -> Result<(), RelayError> {
+        let path = format!("/api/devices/{}", encode_path_segment(device_id));
+        self.send::<serde_json::Value>(self.request(reqwest::Method::DELETE, &path)).await?;
+        Ok(())
+    }
+
+    pub async fn pairing_code(&self) -> Result<PairingCode, RelayError> {
+        self.post("/api/auth/pairing-code", &serde_json::json!({})).await
+    }
+
+    // Admin endpoints answer 403 to any token that isn't the relay's
+    // AUTH_TOKEN; a 401 here must not expire the user's own session.
+
+    pub async fn list_users(&self) -> Result<Vec<AdminUser>, RelayError> {
+        let response: UsersResponse = admin_result(self.get("/api/admin/users").await)?;
+        Ok(response.users)
+    }
+
+    pub async fn approve_user(&self, id: i64) -> Result<(), RelayError> {
+        self.set_user_status(id, "approve").await
+    }
+
+    pub async fn reject_user(&self, id: i64) -> Result<(), RelayError> {
+        self.set_user_status(id, "reject").await
+    }
+
+    async fn set_user_status(&self, id: i64, action: &str) -> Result<(), RelayError> {
+        let path = format!("/api/admin/users/{}/{}", id, action);
+        admin_result(self.post::<_, serde_json::Value>(&path, &serde_json::json!({})).await)?;
+        Ok(())
+    }
+
+    pub async fn is_healthy(&self) -> bool {
+        self.get::<serde_json::Value>("/health").await.is_ok()
+    }
+}
+
+fn build_http_client(network: &NetworkSettings) -> reqwest::Client {
+    crate::network::client_builder(network)
+        .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS))
+        .connect_timeout(Duration::from_secs(CONNECT_TIMEOUT_SECS))
+        .user_agent(concat!("remote-clauding-desktop/", env!("CARGO_PKG_VERSION")))
+        .build()
+        .unwrap_or_else(|_| reqwest::Client::new())
+}
+
+/// Percent-encodes everything but RFC 3986 unreserved characters.
+fn encode_path_segment(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => out.push(byte as char),
+            _ => out.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    out
+}
+
+fn admin_result<T>(result: Result<T, RelayError>) -> Result<T, RelayError> {
+    match result {
+        Err(RelayError::SessionExpired)
+        | Err(RelayError::HttpStatus(401 | 403))
+        | Err(RelayError::Server { status: 401 | 403, .. }) => Err(RelayError::NotAdmin),
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_relay::StandInRelay;
+    use serde_json::json;
+
+    fn device() -> DeviceIdentity {
+        DeviceIdentity {
+            device_id: "dev-1".to_string(),
+            device_name: "Laptop".to_string(),
+        }
+    }
+
+    /// The `Server` status and message of a failed call.
+    fn server_error<T>(result: Result<T, RelayError>) -> (u16, String) {
+        match result {
+            Err(RelayError::Server { status, message }) => (status, message),
+            Err(e) => panic!("expected a server error, got {:?}", e),
+            Ok(_) => panic!("expected a server error, got a success"),
+        }
+    }
+
+    #[tokio::test]
+    async fn resend_code_posts_the_email() {
+        let relay = StandInRelay::start(vec![
+            ("POST /api/auth/resend-code", 200, json!({ "message": "Verification code sent" })),
+            ("POST /api/auth/resend-code", 404, json!({ "error": "User not found" })),
+            ("POST /api/auth/resend-code", 400, json!({ "error": "Email already verified" })),
+        ]);
+        let client = relay.client(None);
+
+        let response = client.resend_code("a@b.c").await.unwrap();
+        assert_eq!(response.message.as_deref(), Some("Verification code sent"));
+        assert_eq!(server_error(client.resend_code("x@y.z").await), (404, "User not found".to_string()));
+        assert_eq!(server_error(client.resend_code("a@b.c").await), (400, "Email already verified".to_string()));
+
+        let requests = relay.requests();
+        assert_eq!(requests[0].body, json!({ "email": "a@b.c" }));
+        assert!(requests[0].authorization.is_none());
+    }
+
+    #[tokio::test]
+    async fn password_reset_request_posts_the_email() {
+        let relay = StandInRelay::start(vec![
+            (
+                "POST /api/auth/password-reset/request",
+                200,
+                json!({ "message": "If the account exists, a reset code has been sent" }),
+            ),
+            ("POST /api/auth/password-reset/request", 429, json!({ "error": "Too many attempts, try again later" })),
+            ("POST /api/auth/password-reset/request", 502, json!({})),
+        ]);
+        let client = relay.client(None);
+
+        let response = client.request_password_reset("a@b.c").await.unwrap();
+        assert!(response.auth_token.is_none());
+        assert_eq!(
+            server_error(client.request_password_reset("a@b.c").await),
+            (429, "Too many attempts, try again later".to_string())
+        );
+        assert!(matches!(client.request_password_reset("a@b.c").await, Err(RelayError::HttpStatus(502))));
+
+        assert_eq!(relay.requests()[0].body, json!({ "email": "a@b.c" }));
+    }
+
+    #[tokio::test]
+    async fn password_reset_confirm_sends_device_and_returns_token() {
+        let relay = StandInRelay::start(vec![(
+            "POST /api/auth/password-reset/confirm",
+            200,
+            json!({ "message": "Password updated.", "auth_token": "tok-2" }),
+        )]);
+
+        let response = relay
+            .client(None)
+            .confirm_password_reset("a@b.c", "123456", "secret1", &device())
+            .await
+            .unwrap();
+        assert_eq!(response.auth_token.as_deref(), Some("tok-2"));
+
+        let requests = relay.requests();
+        assert_eq!(
+            requests[0].body,
+            json!({ "email": "a@b.c", "code": "123456", "password": "secret1", "device_id": "dev-1", "device_name": "Laptop" })
+        );
+        assert!(requests[0].authorization.is_none());
+    }
+
+    #[tokio::test]
+    async fn password_reset_confirm_surfaces_relay_refusals() {
+        let refusals = [
+            (400, "Too many wrong codes, request a new one"),
+            (429, "Too many attempts, try again later"),
+            (403, "Account pending admin approval"),
+        ];
+        let relay = StandInRelay::start(
+            refusals
+                .iter()
+                .map(|(status, message)| ("POST /api/auth/password-reset/confirm", *status, json!({ "error": message })))
+                .collect(),
+        );
+        let client = relay.client(None);
+
+        for (status, message) in refusals {
+            let result = client.confirm_password_reset("a@b.c", "000000", "secret1", &device()).await;
+            assert_eq!(server_error(result), (status, message.to_string()));
+        }
+    }
+
+    #[tokio::test]
+    async fn blocked_account_is_not_an_expired_session() {
+        let relay = StandInRelay::start(vec![
+            (
+                "GET /api/devices",
+                403,
+                json!({ "error": "Account pending admin approval", "code": "account_blocked" }),
+            ),
+            ("GET /api/devices", 401, json!({ "error": "Unauthorized" })),
+        ]);
+        let client = relay.client(Some("tok-1"));
+
+        match client.list_devices().await {
+            Err(RelayError::AccountBlocked(reason)) => assert_eq!(reason, "Account pending admin approval"),
+            other => panic!("expected a blocked account, got {:?}", other.map(|d| d.len())),
+        }
+        assert!(matches!(client.list_devices().await, Err(RelayError::SessionExpired)));
+        assert_eq!(relay.requests()[0].authorization.as_deref(), Some("Bearer tok-1"));
+    }
+
+    #[test]
+    fn blocked_account_serializes_with_its_own_code() {
+        let error = RelayError::AccountBlocked("Email not verified".to_string());
+        assert_eq!(
+            serde_json::to_value(&error).unwrap(),
+            json!({ "code": "account_blocked", "message": "Email not verified", "status": null })
+        );
+    }
+
+    #[tokio::test]
+    async fn admin_lists_users_and_sets_their_status() {
+        let relay = StandInRelay::start(vec![
+            (
+                "GET /api/admin/users",
+                200,
+                json!({ "users": [
+                    { "id": 7, "email": "a@b.c", "status": "pending", "email_verified": 1, "created_at": "2026-01-01" },
+                    { "id": 8, "email": "d@e.f", "status": "approved", "email_verified": false },
+                ] }),
+            ),
+            ("POST /api/admin/users/7/approve", 200, json!({ "ok": true })),
+            ("POST /api/admin/users/8/reject", 200, json!({ "ok": true })),
+        ]);
+        let client = relay.client(Some("admin-tok"));
+
+        let users = client.list_users().await.unwrap();
+        assert_eq!(users.len(), 2);
+        assert_eq!((users[0].id, users[0].status.as_str(), users[0].email_verified), (7, "pending", true));
+        assert!(!users[1].email_verified);
+        assert!(users[1].created_at.is_none());
+
+        client.approve_user(7).await.unwrap();
+        client.reject_user(8).await.unwrap();
+        assert!(relay.requests().iter().all(|r| r.authorization.as_deref() == Some("Bearer admin-tok")));
+    }
+
+    #[tokio::test]
+    async fn admin_refusals_become_not_admin() {
+        let relay = StandInRelay::start(vec![
+            ("GET /api/admin/users", 401, json!({ "error": "Unauthorized" })),
+            ("POST /api/admin/users/7/approve", 403, json!({ "error": "Admin access required" })),
+            ("POST /api/admin/users/7/reject", 403, json!({})),
+            ("POST /api/admin/users/99/approve", 404, json!({ "error": "User not found" })),
+        ]);
+        let client = relay.client(Some("user-tok"));
+
+        assert!(matches!(client.list_users().await, Err(RelayError::NotAdmin)));
+        assert!(matches!(client.approve_user(7).await, Err(RelayError::NotAdmin)));
+        assert!(matches!(client.reject_user(7).await, Err(RelayError::NotAdmin)));
+        assert_eq!(server_error(client.approve_user(99).await), (404, "User not found".to_string()));
+    }
+
+    #[tokio::test]
+    async fn device_endpoints_list_rename_and_revoke() {
+        let relay = StandInRelay::start(vec![
+            (
+                "GET /api/devices",
+                200,
+                json!({ "devices": [
+                    { "device_id": "dev-1", "name": "Laptop", "created_at": "2026-01-01", "last_seen_at": null, "current": true },
+                    { "device_id": "phone/2", "name": "Phone" },
+                ] }),
+            ),
+            ("PATCH /api/devices/dev-1", 200, json!({ "ok": true })),
+            ("DELETE /api/devices/phone%2F2", 200, json!({ "ok": true })),
+        ]);
+        let client = relay.client(Some("tok-1"));
+
+        let devices = client.list_devices().await.unwrap();
+        assert_eq!(devices.len(), 2);
+        assert!(devices[0].current);
+        assert_eq!(devices[0].created_at.as_deref(), Some("2026-01-01"));
+        assert!(!devices[1].current);
+        assert!(devices[1].last_seen_at.is_none());
+
+        client.rename_device("dev-1", "Work laptop").await.unwrap();
+        client.revoke_device("phone/2").await.unwrap();
+
+        let requests = relay.requests();
+        assert_eq!(requests[1].body, json!({ "name": "Work laptop" }));
+        assert!(requests.iter().all(|r| r.authorization.as_deref() == Some("Bearer tok-1")));
+    }
+
+    #[tokio::test]
+    async fn device_refusals_keep_the_relay_message() {
+        let relay = StandInRelay::start(vec![
+            (
+                "DELETE /api/devices/dev-1",
+                400,
+                json!({ "error": "Cannot revoke the current device; log out instead" }),
+            ),
+            ("PATCH /api/devices/gone", 404, json!({ "error": "Device not found" })),
+        ]);
+        let client = relay.client(Some("tok-1"));
+
+        assert_eq!(
+            server_error(client.revoke_device("dev-1").await),
+            (400, "Cannot revoke the current device; log out instead".to_string())
+        );
+        assert_eq!(server_error(client.rename_device("gone", "X").await), (404, "Device not found".to_string()));
+    }
+
+    #[tokio::test]
+    async fn device_code_sends_the_device_and_parses_the_grant() {
+        let relay = StandInRelay::start(vec![(
+            "POST /api/auth/device-code",
+            200,
+            json!({
+                "device_code": "dc-1",
+                "user_code": "ABCD-EFGH",
+                "verification_uri": "https://relay.test/device",
+                "verification_uri_complete": "https://relay.test/device?code=ABCD-EFGH",
+                "expires_in": 600,
+                "interval": 5,
+            }),
+        )]);
+
+        let code = relay.client(None).request_device_code(&device()).await.unwrap();
+        assert_eq!((code.device_code.as_str(), code.user_code.as_str()), ("dc-1", "ABCD-EFGH"));
+        assert_eq!(code.verification_uri, "https://relay.test/device");
+        assert_eq!(code.verification_uri_complete.as_deref(), Some("https://relay.test/device?code=ABCD-EFGH"));
+        assert_eq!((code.expires_in, code.interval), (600, 5));
+
+        let requests = relay.requests();
+        assert_eq!(requests[0].body, json!({ "device_id": "dev-1", "device_name": "Laptop" }));
+        assert!(requests[0].authorization.is_none());
+    }
+
+    #[tokio::test]
+    async fn device_token_reports_pending_states_until_approved() {
+        let relay = StandInRelay::start(vec![
+            ("POST /api/auth/device-token", 400, json!({ "error": "authorization_pending" })),
+            ("POST /api/auth/device-token", 400, json!({ "error": "slow_down" })),
+            ("POST /api/auth/device-token", 200, json!({ "auth_token": "tok-3" })),
+        ]);
+        let client = relay.client(None);
+
+        for expected in ["authorization_pending", "slow_down"] {
+            assert_eq!(server_error(client.poll_device_token("dc-1").await), (400, expected.to_string()));
+        }
+        let response = client.poll_device_token("dc-1").await.unwrap();
+        assert_eq!(response.auth_token.as_deref(), Some("tok-3"));
+        assert!(relay.requests().iter().all(|r| r.body == json!({ "device_code": "dc-1" })));
+    }
+}
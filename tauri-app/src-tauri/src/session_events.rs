@@ -0,0 +1,208 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::Emitter;
+
+use crate::agent_client::AgentSession;
+
+// ── Live session events from the agent's local WebSocket (/ws) ──
+
+const RECONNECT_MIN_SECS: u64 = 1;
+const RECONNECT_MAX_SECS: u64 = 30;
+/// Output messages kept per session for transcript export
+const TRANSCRIPT_LIMIT: usize = 2000;
+
+/// Messages broadcast by lib/session-manager.js to local clients.
+#[derive(Deserialize, Serialize, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AgentEvent {
+    ClaudeOutput {
+        #[serde(alias = "sessionId")]
+        session_id: String,
+        message: serde_json::Value,
+    },
+    SessionStatus {
+        #[serde(alias = "sessionId")]
+        session_id: String,
+        status: String,
+    },
+    InputRequired {
+        #[serde(alias = "sessionId")]
+        session_id: String,
+        #[serde(default)]
+        prompt: serde_json::Value,
+    },
+    SessionsUpdated {
+        sessions: Vec<AgentSession>,
+    },
+    AutoAcceptChanged {
+        #[serde(alias = "sessionId")]
+        session_id: String,
+        #[serde(alias = "autoAccept")]
+        auto_accept: bool,
+    },
+}
+
+impl AgentEvent {
+    /// Tauri event name the frontend listens on.
+    fn event_name(&self) -> &'static str {
+        match self {
+            AgentEvent::ClaudeOutput { .. } => "session_output",
+            AgentEvent::SessionStatus { .. } => "session_status",
+            AgentEvent::InputRequired { .. } => "session_input_required",
+            AgentEvent::SessionsUpdated { .. } => "sessions_updated",
+            AgentEvent::AutoAcceptChanged { .. } => "session_auto_accept_changed",
+        }
+    }
+}
+
+#[derive(Serialize, Clone)]
+pub struct SessionSnapshot {
+    pub session: AgentSession,
+    pub pending_input: Option<serde_json::Value>,
+}
+
+pub type EventHandler = Box<dyn Fn(&tauri::AppHandle, &AgentEvent) + Send + Sync>;
+
+/// Latest known state per session, the output seen since the app started,
+/// and the handlers run for every event.
+#[derive(Default)]
+pub struct SessionEvents {
+    sessions: Mutex<HashMap<String, SessionSnapshot>>,
+    transcripts: Mutex<HashMap<String, Vec<serde_json::Value>>>,
+    handlers: Mutex<Vec<EventHandler>>,
+}
+
+impl SessionEvents {
+    pub fn add_handler(&self, handler: EventHandler) {
+        if let Ok(mut handlers) = self.handlers.lock() {
+            handlers.push(handler);
+        }
+    }
+
+    pub fn snapshots(&self) -> Vec<SessionSnapshot> {
+        self.sessions
+            .lock()
+            .map(|s| s.values().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    pub fn session(&self, session_id: &str) -> Option<SessionSnapshot> {
+        self.sessions.lock().ok().and_then(|s| s.get(session_id).cloned())
+    }
+
+    /// Output messages received for `session_id`, oldest first.
+    pub fn transcript(&self, session_id: &str) -> Vec<serde_json::Value> {
+        self.transcripts
+            .lock()
+            .ok()
+            .and_then(|t| t.get(session_id).cloned())
+            .unwrap_or_default()
+    }
+
+    fn record(&self, session_id: &str, message: &serde_json::Value) {
+        let Ok(mut transcripts) = self.transcripts.lock() else {
+            return;
+        };
+        let mut message = message.clone();
+        if message.is_object() && message["timestamp"].is_null() {
+            message["timestamp"] = serde_json::json!(crate::now_millis());
+        }
+        let messages = transcripts.entry(session_id.to_string()).or_default();
+        messages.push(message);
+        if messages.len() > TRANSCRIPT_LIMIT {
+            let excess = messages.len() - TRANSCRIPT_LIMIT;
+            messages.drain(..excess);
+        }
+    }
+
+    fn apply(&self, event: &AgentEvent) {
+        if let AgentEvent::ClaudeOutput { session_id, message } = event {
+            self.record(session_id, message);
+        }
+
+        let Ok(mut sessions) = self.sessions.lock() else {
+            return;
+        };
+        match event {
+            AgentEvent::SessionsUpdated { sessions: list } => {
+                let mut next = HashMap::new();
+                for session in list {
+                    let pending_input = sessions.get(&session.id).and_then(|s| s.pending_input.clone());
+                    next.insert(
+                        session.id.clone(),
+                        SessionSnapshot {
+                            session: session.clone(),
+                            pending_input,
+                        },
+                    );
+                }
+                *sessions = next;
+            }
+            AgentEvent::SessionStatus { session_id, status } => {
+                if let Some(snapshot) = sessions.get_mut(session_id) {
+                    snapshot.session.status = status.clone();
+                    if status != "input_required" {
+                        snapshot.pending_input = None;
+                    }
+                }
+            }
+            AgentEvent::InputRequired { session_id, prompt } => {
+                if let Some(snapshot) = sessions.get_mut(session_id) {
+                    snapshot.session.status = "input_required".to_string();
+                    snapshot.pending_input = Some(prompt.clone());
+                }
+            }
+            AgentEvent::ClaudeOutput { .. } | AgentEvent::AutoAcceptChanged { .. } => {}
+        }
+    }
+
+    fn dispatch(&self, app: &tauri::AppHandle, event: &AgentEvent) {
+        self.apply(event);
+
+        let _ = app.emit(event.event_name(), event);
+
+        if let Ok(handlers) = self.handlers.lock() {
+            for handler in handlers.iter() {
+                handler(app, event);
+            }
+        }
+    }
+}
+
+/// Connects to the agent and re-emits its events until the app exits,
+/// reconnecting with backoff whenever the agent is down or restarts.
+pub fn spawn_listener(app: tauri::AppHandle, events: Arc<SessionEvents>, port: fn() -> u16) {
+    std::thread::spawn(move || {
+        let mut delay = RECONNECT_MIN_SECS;
+        loop {
+            let url = format!("ws://127.0.0.1:{}/ws", port());
+            if let Ok((mut socket, _)) = tungstenite::connect(url.as_str()) {
+                log::info!("Connected to agent event feed at {}", url);
+                delay = RECONNECT_MIN_SECS;
+                let _ = app.emit("agent_events_connected", true);
+
+                while let Ok(message) = socket.read() {
+                    let text = match message {
+                        tungstenite::Message::Text(text) => text,
+                        tungstenite::Message::Close(_) => break,
+                        _ => continue,
+                    };
+                    match serde_json::from_str::<AgentEvent>(&text) {
+                        Ok(event) => events.dispatch(&app, &event),
+                        Err(e) => log::debug!("Ignoring agent message: {}", e),
+                    }
+                }
+
+                if let Ok(mut sessions) = events.sessions.lock() {
+                    sessions.clear();
+                }
+                let _ = app.emit("agent_events_connected", false);
+            }
+
+            std::thread::sleep(Duration::from_secs(delay));
+            delay = (delay * 2).min(RECONNECT_MAX_SECS);
+        }
+    });
+}
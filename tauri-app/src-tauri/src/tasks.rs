@@ -0,0 +1,99 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use tauri::Emitter;
+use tokio_util::sync::CancellationToken;
+
+// ── Long-running operations (downloads, npm) tracked by ID ──
+
+#[derive(Serialize, Clone)]
+pub struct TaskInfo {
+    pub id: String,
+    pub kind: String,
+    /// Unix time in milliseconds
+    pub started_at: u64,
+}
+
+struct TaskEntry {
+    info: TaskInfo,
+    cancel: CancellationToken,
+}
+
+#[derive(Default)]
+pub struct TaskManager {
+    next_id: AtomicU64,
+    tasks: Mutex<HashMap<String, TaskEntry>>,
+}
+
+pub const CANCELLED: &str = "Cancelled";
+
+impl TaskManager {
+    pub fn list(&self) -> Vec<TaskInfo> {
+        self.tasks
+            .lock()
+            .map(|t| t.values().map(|e| e.info.clone()).collect())
+            .unwrap_or_default()
+    }
+
+    /// Returns false if no task with that ID is running.
+    pub fn cancel(&self, id: &str) -> bool {
+        match self.tasks.lock().ok().and_then(|t| t.get(id).map(|e| e.cancel.clone())) {
+            Some(token) => {
+                token.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Runs the future built by `make` as task `kind`, emitting `task_started`
+    /// and `task_finished`. Cancelling drops the future, so anything it owns
+    /// (e.g. a child spawned with `kill_on_drop`) is torn down with it; the
+    /// result is then `Err(CANCELLED)`. Work moved off the runtime outlives
+    /// the future, so `make` also gets the token to check.
+    pub async fn run<F, Fut, T>(&self, window: &tauri::Window, kind: &str, make: F) -> Result<T, String>
+    where
+        F: FnOnce(String, CancellationToken) -> Fut,
+        Fut: Future<Output = Result<T, String>>,
+    {
+        let id = format!("{}-{}", kind, self.next_id.fetch_add(1, Ordering::Relaxed) + 1);
+        let cancel = CancellationToken::new();
+        let info = TaskInfo {
+            id: id.clone(),
+            kind: kind.to_string(),
+            started_at: crate::now_millis(),
+        };
+        if let Ok(mut tasks) = self.tasks.lock() {
+            tasks.insert(
+                id.clone(),
+                TaskEntry {
+                    info: info.clone(),
+                    cancel: cancel.clone(),
+                },
+            );
+        }
+        let _ = window.emit("task_started", &info);
+
+        let result = tokio::select! {
+            result = make(id.clone(), cancel.clone()) => result,
+            _ = cancel.cancelled() => Err(CANCELLED.to_string()),
+        };
+
+        if let Ok(mut tasks) = self.tasks.lock() {
+            tasks.remove(&id);
+        }
+        let status = match &result {
+            Ok(_) => "done",
+            Err(e) if e == CANCELLED => "cancelled",
+            Err(_) => "error",
+        };
+        let _ = window.emit(
+            "task_finished",
+            serde_json::json!({ "id": id, "kind": kind, "status": status }),
+        );
+
+        result
+    }
+}
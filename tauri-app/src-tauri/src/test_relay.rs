@@ -0,0 +1,115 @@
+use std::io::{BufRead, BufReader, Write};
+use std::net::TcpListener;
+use std::sync::{Arc, Mutex};
+
+use crate::network::NetworkSettings;
+use crate::relay_client::RelayClient;
+
+// ── Stand-in relay for tests: canned JSON answers per route, recorded requests ──
+
+pub struct Request {
+    pub method: String,
+    pub path: String,
+    pub authorization: Option<String>,
+    pub body: serde_json::Value,
+}
+
+pub struct StandInRelay {
+    pub url: String,
+    requests: Arc<Mutex<Vec<Request>>>,
+}
+
+impl StandInRelay {
+    /// Answers each request with the first unused `(route, status, body)`
+    /// whose route (e.g. `"POST /api/auth/login"`) matches it, and with 404
+    /// if none does. Stops listening once every answer has been served.
+    pub fn start(routes: Vec<(&str, u16, serde_json::Value)>) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+
+        let mut routes: Vec<(String, u16, serde_json::Value)> = routes
+            .into_iter()
+            .map(|(route, status, body)| (route.to_string(), status, body))
+            .collect();
+        let recorded = requests.clone();
+        std::thread::spawn(move || {
+            while !routes.is_empty() {
+                let Ok((stream, _)) = listener.accept() else {
+                    return;
+                };
+                let mut reader = BufReader::new(stream);
+                let request = read_request(&mut reader);
+                let route = format!("{} {}", request.method, request.path);
+                recorded.lock().unwrap().push(request);
+
+                let (status, body) = match routes.iter().position(|(r, _, _)| *r == route) {
+                    Some(i) => {
+                        let (_, status, body) = routes.remove(i);
+                        (status, body)
+                    }
+                    None => (404, serde_json::json!({ "error": format!("No stand-in answer for {}", route) })),
+                };
+                let body = body.to_string();
+                let response = format!(
+                    "HTTP/1.1 {} Stand-in\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    status,
+                    body.len(),
+                    body
+                );
+                let _ = reader.get_mut().write_all(response.as_bytes());
+            }
+        });
+
+        StandInRelay { url, requests }
+    }
+
+    /// A client of this relay, sending `token` if one is given. It goes
+    /// direct whatever the developer's own proxy settings say.
+    pub fn client(&self, token: Option<&str>) -> RelayClient {
+        let client = RelayClient::with_base_url(&self.url, &NetworkSettings::default());
+        match token {
+            Some(token) => client.with_token(token.to_string()),
+            None => client,
+        }
+    }
+
+    pub fn requests(&self) -> std::sync::MutexGuard<'_, Vec<Request>> {
+        self.requests.lock().unwrap()
+    }
+}
+
+fn read_request(reader: &mut impl BufRead) -> Request {
+    let mut line = String::new();
+    reader.read_line(&mut line).unwrap();
+    let mut parts = line.split_whitespace();
+    let method = parts.next().unwrap_or_default().to_string();
+    let path = parts.next().unwrap_or_default().to_string();
+
+    let mut content_length = 0;
+    let mut authorization = None;
+    loop {
+        let mut header = String::new();
+        reader.read_line(&mut header).unwrap();
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            match name.to_ascii_lowercase().as_str() {
+                "content-length" => content_length = value.trim().parse().unwrap_or(0),
+                "authorization" => authorization = Some(value.trim().to_string()),
+                _ => {}
+            }
+        }
+    }
+
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body).unwrap();
+    Request {
+        method,
+        path,
+        authorization,
+        body: serde_json::from_slice(&body).unwrap_or_default(),
+    }
+}
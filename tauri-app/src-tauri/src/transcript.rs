@@ -0,0 +1,543 @@
+use serde::Serialize;
+use std::time::Duration;
+
+// ── Session transcript export (Markdown, JSON Lines, HTML) ──
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Markdown,
+    JsonLines,
+    Html,
+}
+
+impl ExportFormat {
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value {
+            "markdown" | "md" => Ok(ExportFormat::Markdown),
+            "jsonl" | "json_lines" => Ok(ExportFormat::JsonLines),
+            "html" => Ok(ExportFormat::Html),
+            other => Err(format!("Unknown export format: {}", other)),
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Markdown => "md",
+            ExportFormat::JsonLines => "jsonl",
+            ExportFormat::Html => "html",
+        }
+    }
+}
+
+pub struct TranscriptMeta {
+    pub session_id: String,
+    pub project_name: String,
+    pub project_path: String,
+}
+
+/// One conversation step, built from the raw `claude_output` messages the
+/// agent emits (deltas merged, tool results and permission decisions
+/// folded into the call they belong to).
+#[derive(Serialize, Clone, PartialEq, Debug)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum TranscriptItem {
+    User {
+        timestamp: Option<u64>,
+        text: String,
+    },
+    Assistant {
+        timestamp: Option<u64>,
+        text: String,
+    },
+    ToolCall {
+        timestamp: Option<u64>,
+        tool_id: String,
+        tool: String,
+        summary: String,
+        input: serde_json::Value,
+        result: Option<String>,
+        is_error: bool,
+    },
+    Permission {
+        timestamp: Option<u64>,
+        permission_id: String,
+        tool: String,
+        summary: String,
+        input: serde_json::Value,
+        decision: Option<String>,
+    },
+    Question {
+        timestamp: Option<u64>,
+        questions: Vec<String>,
+    },
+    Error {
+        timestamp: Option<u64>,
+        text: String,
+    },
+}
+
+fn text_of(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(s) => s.clone(),
+        serde_json::Value::Array(blocks) => blocks
+            .iter()
+            .map(|b| b["text"].as_str().map(|s| s.to_string()).unwrap_or_else(|| text_of(b)))
+            .collect::<Vec<_>>()
+            .join("\n"),
+        serde_json::Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+pub fn build_items(messages: &[serde_json::Value]) -> Vec<TranscriptItem> {
+    let mut items: Vec<TranscriptItem> = Vec::new();
+    // Deltas extend the assistant block started by text_block_start
+    let mut in_delta_block = false;
+
+    for msg in messages {
+        let timestamp = msg["timestamp"].as_u64();
+        let kind = msg["type"].as_str().unwrap_or_default();
+
+        if msg["role"] == "user" && kind.is_empty() {
+            in_delta_block = false;
+            items.push(TranscriptItem::User {
+                timestamp,
+                text: text_of(&msg["content"]),
+            });
+            continue;
+        }
+
+        match kind {
+            "text_block_start" => in_delta_block = false,
+            "assistant_delta" => {
+                let chunk = text_of(&msg["content"]);
+                match items.last_mut() {
+                    Some(TranscriptItem::Assistant { text, .. }) if in_delta_block => text.push_str(&chunk),
+                    _ => items.push(TranscriptItem::Assistant { timestamp, text: chunk }),
+                }
+                in_delta_block = true;
+            }
+            "assistant_message" => {
+                in_delta_block = false;
+                items.push(TranscriptItem::Assistant {
+                    timestamp,
+                    text: text_of(&msg["content"]),
+                });
+            }
+            "tool_use_start" | "tool_use_update" => {
+                in_delta_block = false;
+                let tool_id = msg["toolId"].as_str().unwrap_or_default().to_string();
+                let existing = items.iter_mut().rev().find_map(|item| match item {
+                    TranscriptItem::ToolCall { tool_id: id, input, summary, .. } if *id == tool_id && !tool_id.is_empty() => {
+                        Some((input, summary))
+                    }
+                    _ => None,
+                });
+                match existing {
+                    Some((input, summary)) => {
+                        if !msg["toolInput"].is_null() {
+                            *input = msg["toolInput"].clone();
+                        }
+                        if let Some(s) = msg["summary"].as_str() {
+                            *summary = s.to_string();
+                        }
+                    }
+                    None => items.push(TranscriptItem::ToolCall {
+                        timestamp,
+                        tool_id,
+                        tool: msg["toolName"].as_str().unwrap_or_default().to_string(),
+                        summary: msg["summary"].as_str().unwrap_or_default().to_string(),
+                        input: msg["toolInput"].clone(),
+                        result: None,
+                        is_error: false,
+                    }),
+                }
+            }
+            "tool_result" => {
+                let tool_id = msg["toolId"].as_str().unwrap_or_default();
+                for item in items.iter_mut().rev() {
+                    if let TranscriptItem::ToolCall { tool_id: id, result, is_error, .. } = item {
+                        if id == tool_id {
+                            *result = Some(text_of(&msg["content"]));
+                            *is_error = msg["isError"].as_bool().unwrap_or(false);
+                            break;
+                        }
+                    }
+                }
+            }
+            "permission_request" => {
+                in_delta_block = false;
+                items.push(TranscriptItem::Permission {
+                    timestamp,
+                    permission_id: msg["permissionId"].as_str().unwrap_or_default().to_string(),
+                    tool: msg["toolName"].as_str().unwrap_or_default().to_string(),
+                    summary: msg["summary"].as_str().unwrap_or_default().to_string(),
+                    input: msg["toolInput"].clone(),
+                    decision: None,
+                });
+            }
+            "permission_resolved" => {
+                let permission_id = msg["permissionId"].as_str().unwrap_or_default();
+                for item in items.iter_mut().rev() {
+                    if let TranscriptItem::Permission { permission_id: id, decision, .. } = item {
+                        if id == permission_id {
+                            *decision = msg["action"].as_str().map(|s| s.to_string());
+                            break;
+                        }
+                    }
+                }
+            }
+            "ask_question" => {
+                in_delta_block = false;
+                let questions = msg["questions"]
+                    .as_array()
+                    .map(|qs| {
+                        qs.iter()
+                            .filter_map(|q| q["question"].as_str().map(|s| s.to_string()))
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                items.push(TranscriptItem::Question { timestamp, questions });
+            }
+            "error" => {
+                in_delta_block = false;
+                items.push(TranscriptItem::Error {
+                    timestamp,
+                    text: text_of(&msg["content"]),
+                });
+            }
+            _ => {}
+        }
+    }
+
+    items
+}
+
+fn format_timestamp(timestamp: Option<u64>) -> String {
+    timestamp
+        .and_then(|ms| chrono::DateTime::from_timestamp_millis(ms as i64))
+        .map(|t| t.format("%Y-%m-%d %H:%M:%S UTC").to_string())
+        .unwrap_or_default()
+}
+
+fn pretty_json(value: &serde_json::Value) -> String {
+    serde_json::to_string_pretty(value).unwrap_or_default()
+}
+
+pub fn render_markdown(meta: &TranscriptMeta, items: &[TranscriptItem]) -> String {
+    let mut out = format!(
+        "# {}\n\n- Project: `{}`\n- Session: `{}`\n",
+        meta.project_name, meta.project_path, meta.session_id
+    );
+
+    for item in items {
+        out.push('\n');
+        match item {
+            TranscriptItem::User { timestamp, text } => {
+                out.push_str(&format!("## You — {}\n\n{}\n", format_timestamp(*timestamp), text));
+            }
+            TranscriptItem::Assistant { timestamp, text } => {
+                out.push_str(&format!("## Claude — {}\n\n{}\n", format_timestamp(*timestamp), text));
+            }
+            TranscriptItem::ToolCall { timestamp, tool, summary, input, result, is_error, .. } => {
+                out.push_str(&format!(
+                    "### Tool: {} — {}\n\n{}\n\n```json\n{}\n```\n",
+                    tool,
+                    format_timestamp(*timestamp),
+                    summary,
+                    pretty_json(input)
+                ));
+                if let Some(result) = result {
+                    let label = if *is_error { "Error" } else { "Result" };
+                    out.push_str(&format!("\n{}:\n\n```\n{}\n```\n", label, result));
+                }
+            }
+            TranscriptItem::Permission { timestamp, tool, summary, decision, .. } => {
+                out.push_str(&format!(
+                    "### Permission: {} — {}\n\n{}\n\nDecision: **{}**\n",
+                    tool,
+                    format_timestamp(*timestamp),
+                    summary,
+                    decision.as_deref().unwrap_or("pending")
+                ));
+            }
+            TranscriptItem::Question { timestamp, questions } => {
+                out.push_str(&format!("### Question — {}\n\n", format_timestamp(*timestamp)));
+                for q in questions {
+                    out.push_str(&format!("> {}\n", q));
+                }
+            }
+            TranscriptItem::Error { timestamp, text } => {
+                out.push_str(&format!("### Error — {}\n\n{}\n", format_timestamp(*timestamp), text));
+            }
+        }
+    }
+
+    out
+}
+
+pub fn render_jsonl(meta: &TranscriptMeta, items: &[TranscriptItem]) -> String {
+    let mut out = serde_json::json!({
+        "kind": "session",
+        "session_id": meta.session_id,
+        "project_name": meta.project_name,
+        "project_path": meta.project_path,
+    })
+    .to_string();
+    out.push('\n');
+
+    for item in items {
+        if let Ok(line) = serde_json::to_string(item) {
+            out.push_str(&line);
+            out.push('\n');
+        }
+    }
+    out
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+pub fn render_html(meta: &TranscriptMeta, items: &[TranscriptItem]) -> String {
+    let mut body = String::new();
+
+    for item in items {
+        let (class, heading, timestamp, content) = match item {
+            TranscriptItem::User { timestamp, text } => ("user", "You".to_string(), timestamp, format!("<p>{}</p>", escape_html(text))),
+            TranscriptItem::Assistant { timestamp, text } => {
+                ("assistant", "Claude".to_string(), timestamp, format!("<p>{}</p>", escape_html(text)))
+            }
+            TranscriptItem::ToolCall { timestamp, tool, summary, input, result, is_error, .. } => {
+                let mut content = format!(
+                    "<p>{}</p><pre>{}</pre>",
+                    escape_html(summary),
+                    escape_html(&pretty_json(input))
+                );
+                if let Some(result) = result {
+                    content.push_str(&format!(
+                        "<pre class=\"{}\">{}</pre>",
+                        if *is_error { "error" } else { "result" },
+                        escape_html(result)
+                    ));
+                }
+                ("tool", format!("Tool: {}", escape_html(tool)), timestamp, content)
+            }
+            TranscriptItem::Permission { timestamp, tool, summary, decision, .. } => (
+                "permission",
+                format!("Permission: {}", escape_html(tool)),
+                timestamp,
+                format!(
+                    "<p>{}</p><p>Decision: <strong>{}</strong></p>",
+                    escape_html(summary),
+                    escape_html(decision.as_deref().unwrap_or("pending"))
+                ),
+            ),
+            TranscriptItem::Question { timestamp, questions } => (
+                "question",
+                "Question".to_string(),
+                timestamp,
+                questions
+                    .iter()
+                    .map(|q| format!("<blockquote>{}</blockquote>", escape_html(q)))
+                    .collect(),
+            ),
+            TranscriptItem::Error { timestamp, text } => {
+                ("error", "Error".to_string(), timestamp, format!("<p>{}</p>", escape_html(text)))
+            }
+        };
+
+        body.push_str(&format!(
+            "<section class=\"{}\">\n<h2>{} <time>{}</time></h2>\n{}\n</section>\n",
+            class,
+            heading,
+            format_timestamp(*timestamp),
+            content
+        ));
+    }
+
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n<style>\n\
+         body {{ font-family: sans-serif; max-width: 860px; margin: 2em auto; }}\n\
+         section {{ border-left: 3px solid #ccc; padding: 0 1em; margin: 1em 0; }}\n\
+         section.user {{ border-color: #4a90d9; }}\n\
+         section.permission {{ border-color: #e0a030; }}\n\
+         section.error, pre.error {{ border-color: #d9534f; color: #d9534f; }}\n\
+         pre {{ background: #f5f5f5; padding: 0.5em; overflow-x: auto; }}\n\
+         time {{ color: #888; font-size: 0.7em; font-weight: normal; }}\n\
+         </style>\n</head>\n<body>\n<h1>{title}</h1>\n<p>Project: <code>{path}</code><br>Session: <code>{id}</code></p>\n{body}</body>\n</html>\n",
+        title = escape_html(&meta.project_name),
+        path = escape_html(&meta.project_path),
+        id = escape_html(&meta.session_id),
+        body = body
+    )
+}
+
+pub fn render(format: ExportFormat, meta: &TranscriptMeta, messages: &[serde_json::Value]) -> String {
+    let items = build_items(messages);
+    match format {
+        ExportFormat::Markdown => render_markdown(meta, &items),
+        ExportFormat::JsonLines => render_jsonl(meta, &items),
+        ExportFormat::Html => render_html(meta, &items),
+    }
+}
+
+/// Fetches a session's message history from the relay the way the PWA does:
+/// subscribe over `/ws/client` and wait for `message_history`.
+pub fn fetch_relay_history(
+    network: &crate::network::NetworkSettings,
+    relay_ws_url: &str,
+    token: &str,
+    session_id: &str,
+) -> Result<Vec<serde_json::Value>, String> {
+    let mut url = reqwest::Url::parse(&format!("{}/ws/client", relay_ws_url))
+        .map_err(|e| format!("Invalid relay URL {}: {}", relay_ws_url, e))?;
+    url.query_pairs_mut().append_pair("token", token);
+    let mut socket = crate::network::connect_websocket(network, &url).map_err(|e| format!("Relay not reachable: {}", e))?;
+
+    match socket.get_ref() {
+        tungstenite::stream::MaybeTlsStream::Plain(s) => {
+            let _ = s.set_read_timeout(Some(Duration::from_secs(10)));
+        }
+        tungstenite::stream::MaybeTlsStream::NativeTls(s) => {
+            let _ = s.get_ref().set_read_timeout(Some(Duration::from_secs(10)));
+        }
+        _ => {}
+    }
+
+    let subscribe = serde_json::json!({ "type": "subscribe_session", "sessionId": session_id });
+    socket
+        .send(tungstenite::Message::Text(subscribe.to_string()))
+        .map_err(|e| format!("Cannot send to relay: {}", e))?;
+
+    let result = loop {
+        let message = match socket.read() {
+            Ok(tungstenite::Message::Text(text)) => text,
+            Ok(tungstenite::Message::Close(_)) => break Err("Relay closed the connection".to_string()),
+            Ok(_) => continue,
+            Err(e) => break Err(format!("No history from relay: {}", e)),
+        };
+        let msg: serde_json::Value = serde_json::from_str(&message).unwrap_or_default();
+        match msg["type"].as_str() {
+            Some("message_history") if msg["sessionId"] == session_id => {
+                break Ok(msg["messages"].as_array().cloned().unwrap_or_default());
+            }
+            Some("error") => {
+                break Err(msg["error"].as_str().unwrap_or("Relay error").to_string());
+            }
+            _ => {}
+        }
+    };
+
+    let _ = socket.close(None);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use std::path::PathBuf;
+
+    fn meta() -> TranscriptMeta {
+        TranscriptMeta {
+            session_id: "3f2b9c1e".to_string(),
+            project_name: "demo <app>".to_string(),
+            project_path: "/home/u/demo".to_string(),
+        }
+    }
+
+    /// One of everything the agent emits, in the order it emits it.
+    fn messages() -> Vec<serde_json::Value> {
+        vec![
+            json!({ "role": "user", "content": "Fix the <title> & run tests", "timestamp": 1_700_000_000_000u64 }),
+            json!({ "type": "text_block_start", "timestamp": 1_700_000_001_000u64 }),
+            json!({ "type": "assistant_delta", "content": "Looking at ", "timestamp": 1_700_000_001_000u64 }),
+            json!({ "type": "assistant_delta", "content": "the page.", "timestamp": 1_700_000_001_100u64 }),
+            json!({
+                "type": "tool_use_start", "toolId": "t1", "toolName": "Read",
+                "toolInput": null, "summary": "", "timestamp": 1_700_000_002_000u64
+            }),
+            json!({
+                "type": "tool_use_update", "toolId": "t1", "toolName": "Read",
+                "toolInput": { "file_path": "index.html" }, "summary": "index.html"
+            }),
+            json!({ "type": "tool_result", "toolId": "t1", "content": "<title>Old</title>", "isError": false }),
+            json!({
+                "type": "permission_request", "permissionId": "p1", "toolName": "Bash",
+                "toolInput": { "command": "npm test" }, "summary": "npm test", "timestamp": 1_700_000_003_000u64
+            }),
+            json!({ "type": "permission_resolved", "permissionId": "p1", "action": "allow" }),
+            json!({
+                "type": "tool_use_start", "toolId": "t2", "toolName": "Bash",
+                "toolInput": { "command": "npm test" }, "summary": "npm test", "timestamp": 1_700_000_004_000u64
+            }),
+            json!({
+                "type": "tool_result", "toolId": "t2",
+                "content": [{ "type": "text", "text": "1 failing" }], "isError": true
+            }),
+            json!({
+                "type": "ask_question", "timestamp": 1_700_000_005_000u64,
+                "questions": [{ "question": "Update the snapshot?" }]
+            }),
+            json!({ "type": "question_answered" }),
+            json!({ "type": "assistant_message", "content": "Done.", "timestamp": 1_700_000_006_000u64 }),
+            json!({ "type": "error", "content": "Rate limited", "timestamp": 1_700_000_007_000u64 }),
+        ]
+    }
+
+    /// Compares against `tests/golden/<name>`; `UPDATE_GOLDEN=1` rewrites it.
+    fn assert_golden(name: &str, actual: &str) {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests").join("golden").join(name);
+        if std::env::var_os("UPDATE_GOLDEN").is_some() {
+            std::fs::write(&path, actual).unwrap();
+            return;
+        }
+        let expected = std::fs::read_to_string(&path).unwrap_or_else(|e| panic!("{}: {}", path.display(), e));
+        assert_eq!(actual, expected, "{} is out of date (UPDATE_GOLDEN=1 to rewrite)", name);
+    }
+
+    #[test]
+    fn merges_deltas_and_folds_results() {
+        let items = build_items(&messages());
+        assert_eq!(items.len(), 8);
+        assert_eq!(
+            items[1],
+            TranscriptItem::Assistant {
+                timestamp: Some(1_700_000_001_000),
+                text: "Looking at the page.".to_string(),
+            }
+        );
+        match &items[2] {
+            TranscriptItem::ToolCall { summary, input, result, is_error, .. } => {
+                assert_eq!(summary, "index.html");
+                assert_eq!(input, &json!({ "file_path": "index.html" }));
+                assert_eq!(result.as_deref(), Some("<title>Old</title>"));
+                assert!(!is_error);
+            }
+            other => panic!("expected a tool call, got {:?}", other),
+        }
+        match &items[3] {
+            TranscriptItem::Permission { decision, .. } => assert_eq!(decision.as_deref(), Some("allow")),
+            other => panic!("expected a permission, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn markdown_matches_golden() {
+        assert_golden("transcript.md", &render(ExportFormat::Markdown, &meta(), &messages()));
+    }
+
+    #[test]
+    fn jsonl_matches_golden() {
+        assert_golden("transcript.jsonl", &render(ExportFormat::JsonLines, &meta(), &messages()));
+    }
+
+    #[test]
+    fn html_matches_golden() {
+        assert_golden("transcript.html", &render(ExportFormat::Html, &meta(), &messages()));
+    }
+}
@@ -0,0 +1,292 @@
+use rusqlite::{params, params_from_iter, Connection};
+use serde::{Deserialize, Serialize};
+use std::sync::{Arc, Mutex};
+use tauri::Manager;
+
+use crate::session_events::{AgentEvent, SessionEvents};
+
+// ── Token usage and cost aggregates ──
+
+const DB_FILE: &str = "usage.db";
+
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS usage (
+    id INTEGER PRIMARY KEY,
+    session_id TEXT NOT NULL,
+    project_name TEXT NOT NULL DEFAULT '',
+    project_path TEXT NOT NULL DEFAULT '',
+    day TEXT NOT NULL,
+    timestamp INTEGER NOT NULL,
+    input_tokens INTEGER NOT NULL DEFAULT 0,
+    output_tokens INTEGER NOT NULL DEFAULT 0,
+    cache_creation_tokens INTEGER NOT NULL DEFAULT 0,
+    cache_read_tokens INTEGER NOT NULL DEFAULT 0,
+    cost_usd REAL NOT NULL DEFAULT 0,
+    duration_ms INTEGER NOT NULL DEFAULT 0,
+    num_turns INTEGER NOT NULL DEFAULT 0
+);
+CREATE INDEX IF NOT EXISTS usage_day ON usage(day);
+CREATE INDEX IF NOT EXISTS usage_project ON usage(project_path, day);
+";
+
+#[derive(Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum UsageGroup {
+    Session,
+    Project,
+    Day,
+}
+
+impl UsageGroup {
+    fn column(self) -> &'static str {
+        match self {
+            UsageGroup::Session => "session_id",
+            UsageGroup::Project => "project_path",
+            UsageGroup::Day => "day",
+        }
+    }
+}
+
+#[derive(Deserialize, Default)]
+pub struct UsageQuery {
+    #[serde(default)]
+    pub project_path: Option<String>,
+    #[serde(default)]
+    pub session_id: Option<String>,
+    /// Local date "YYYY-MM-DD", inclusive
+    #[serde(default)]
+    pub from: Option<String>,
+    /// Local date "YYYY-MM-DD", inclusive
+    #[serde(default)]
+    pub to: Option<String>,
+}
+
+#[derive(Serialize, Clone, Default)]
+pub struct UsageAggregate {
+    /// Session ID, project path or day, depending on the grouping
+    pub key: String,
+    pub label: String,
+    pub requests: u64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_creation_tokens: u64,
+    pub cache_read_tokens: u64,
+    pub cost_usd: f64,
+    pub duration_ms: u64,
+    pub num_turns: u64,
+}
+
+fn parse_day(value: &str) -> Result<chrono::NaiveDate, String> {
+    chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d").map_err(|_| format!("Invalid date (expected YYYY-MM-DD): {}", value))
+}
+
+pub fn validate(query: &UsageQuery) -> Result<(), String> {
+    for day in [&query.from, &query.to].into_iter().flatten() {
+        parse_day(day)?;
+    }
+    Ok(())
+}
+
+fn local_day(timestamp_ms: u64) -> String {
+    chrono::DateTime::from_timestamp_millis(timestamp_ms as i64)
+        .map(|t| t.with_timezone(&chrono::Local).format("%Y-%m-%d").to_string())
+        .unwrap_or_default()
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+pub fn to_csv(rows: &[UsageAggregate]) -> String {
+    let mut out = String::from(
+        "key,label,requests,input_tokens,output_tokens,cache_creation_tokens,cache_read_tokens,cost_usd,duration_ms,num_turns\n",
+    );
+    for row in rows {
+        out.push_str(&format!(
+            "{},{},{},{},{},{},{},{:.6},{},{}\n",
+            csv_field(&row.key),
+            csv_field(&row.label),
+            row.requests,
+            row.input_tokens,
+            row.output_tokens,
+            row.cache_creation_tokens,
+            row.cache_read_tokens,
+            row.cost_usd,
+            row.duration_ms,
+            row.num_turns
+        ));
+    }
+    out
+}
+
+pub struct UsageStore {
+    conn: Mutex<Connection>,
+}
+
+impl UsageStore {
+    pub fn open() -> Result<Self, String> {
+        crate::ensure_config_dir();
+        let conn = Connection::open(crate::get_config_dir().join(DB_FILE))
+            .map_err(|e| format!("Cannot open usage database: {}", e))?;
+        conn.execute_batch(SCHEMA)
+            .map_err(|e| format!("Cannot create usage database: {}", e))?;
+        Ok(UsageStore { conn: Mutex::new(conn) })
+    }
+
+    fn with_conn<T>(&self, f: impl FnOnce(&Connection) -> rusqlite::Result<T>) -> Result<T, String> {
+        let conn = self.conn.lock().map_err(|_| "Usage database is unavailable".to_string())?;
+        f(&conn).map_err(|e| format!("Usage database error: {}", e))
+    }
+
+    /// Records the usage carried by a `result` message.
+    pub fn record(&self, session_id: &str, project_name: &str, project_path: &str, message: &serde_json::Value) -> Result<(), String> {
+        let usage = &message["usage"];
+        let timestamp = message["timestamp"].as_u64().unwrap_or_else(crate::now_millis);
+        let tokens = |key: &str| usage[key].as_u64().unwrap_or(0) as i64;
+
+        self.with_conn(|conn| {
+            conn.execute(
+                "INSERT INTO usage (session_id, project_name, project_path, day, timestamp, input_tokens, output_tokens,
+                                    cache_creation_tokens, cache_read_tokens, cost_usd, duration_ms, num_turns)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)",
+                params![
+                    session_id,
+                    project_name,
+                    project_path,
+                    local_day(timestamp),
+                    timestamp as i64,
+                    tokens("input_tokens"),
+                    tokens("output_tokens"),
+                    tokens("cache_creation_input_tokens"),
+                    tokens("cache_read_input_tokens"),
+                    message["costUsd"].as_f64().unwrap_or(0.0),
+                    message["durationMs"].as_u64().unwrap_or(0) as i64,
+                    message["numTurns"].as_u64().unwrap_or(0) as i64
+                ],
+            )
+        })?;
+        Ok(())
+    }
+
+    /// Totals grouped by session, project or day, ordered by key (days
+    /// ascending, so the result is directly usable as a time series).
+    pub fn aggregate(&self, group: UsageGroup, query: &UsageQuery) -> Result<Vec<UsageAggregate>, String> {
+        let column = group.column();
+        let label = match group {
+            UsageGroup::Session | UsageGroup::Project => "MAX(project_name)",
+            UsageGroup::Day => "day",
+        };
+        let mut sql = format!(
+            "SELECT {column}, {label}, COUNT(*), SUM(input_tokens), SUM(output_tokens), SUM(cache_creation_tokens),
+                    SUM(cache_read_tokens), SUM(cost_usd), SUM(duration_ms), SUM(num_turns)
+             FROM usage",
+        );
+
+        let mut conditions = Vec::new();
+        let mut values: Vec<rusqlite::types::Value> = Vec::new();
+        if let Some(project_path) = &query.project_path {
+            conditions.push("project_path = ?");
+            values.push(project_path.clone().into());
+        }
+        if let Some(session_id) = &query.session_id {
+            conditions.push("session_id = ?");
+            values.push(session_id.clone().into());
+        }
+        if let Some(from) = &query.from {
+            conditions.push("day >= ?");
+            values.push(from.clone().into());
+        }
+        if let Some(to) = &query.to {
+            conditions.push("day <= ?");
+            values.push(to.clone().into());
+        }
+        if !conditions.is_empty() {
+            sql.push_str(" WHERE ");
+            sql.push_str(&conditions.join(" AND "));
+        }
+        sql.push_str(&format!(" GROUP BY {column} ORDER BY {column}"));
+
+        self.with_conn(|conn| {
+            let mut stmt = conn.prepare(&sql)?;
+            let rows = stmt.query_map(params_from_iter(values), |row| {
+                Ok(UsageAggregate {
+                    key: row.get(0)?,
+                    label: row.get(1)?,
+                    requests: row.get::<_, i64>(2)? as u64,
+                    input_tokens: row.get::<_, i64>(3)? as u64,
+                    output_tokens: row.get::<_, i64>(4)? as u64,
+                    cache_creation_tokens: row.get::<_, i64>(5)? as u64,
+                    cache_read_tokens: row.get::<_, i64>(6)? as u64,
+                    cost_usd: row.get(7)?,
+                    duration_ms: row.get::<_, i64>(8)? as u64,
+                    num_turns: row.get::<_, i64>(9)? as u64,
+                })
+            })?;
+            rows.collect()
+        })
+    }
+
+    /// Per-day totals with zero entries for days without usage, so charts
+    /// get an evenly spaced series.
+    pub fn daily_series(&self, query: &UsageQuery) -> Result<Vec<UsageAggregate>, String> {
+        let rows = self.aggregate(UsageGroup::Day, query)?;
+
+        let first = match &query.from {
+            Some(day) => parse_day(day)?,
+            None => match rows.first() {
+                Some(row) => parse_day(&row.key)?,
+                None => return Ok(rows),
+            },
+        };
+        let last = match &query.to {
+            Some(day) => parse_day(day)?,
+            None => match rows.last() {
+                Some(row) => parse_day(&row.key)?,
+                None => return Ok(rows),
+            },
+        };
+
+        let mut series = Vec::new();
+        let mut rows = rows.into_iter().peekable();
+        let mut day = first;
+        while day <= last {
+            let key = day.format("%Y-%m-%d").to_string();
+            match rows.peek() {
+                Some(row) if row.key == key => series.push(rows.next().unwrap_or_default()),
+                _ => series.push(UsageAggregate {
+                    label: key.clone(),
+                    key,
+                    ..Default::default()
+                }),
+            }
+            day = match day.succ_opt() {
+                Some(next) => next,
+                None => break,
+            };
+        }
+        Ok(series)
+    }
+
+    pub fn handle(&self, app: &tauri::AppHandle, event: &AgentEvent) {
+        let AgentEvent::ClaudeOutput { session_id, message } = event else {
+            return;
+        };
+        if message["type"] != "result" || (message["usage"].is_null() && message["costUsd"].is_null()) {
+            return;
+        }
+
+        let (project_name, project_path) = app
+            .try_state::<Arc<SessionEvents>>()
+            .and_then(|events| events.session(session_id))
+            .map(|s| (s.session.project_name, s.session.project_path))
+            .unwrap_or_default();
+
+        if let Err(e) = self.record(session_id, &project_name, &project_path, message) {
+            log::warn!("Cannot record usage: {}", e);
+        }
+    }
+}
@@ -1,5 +1,6 @@
 import { useState, useEffect } from 'react';
 import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
 import Welcome from './pages/Welcome.jsx';
 import SelectEnvironment from './pages/SelectEnvironment.jsx';
 import Register from './pages/Register.jsx';
@@ -20,13 +21,23 @@ export default function App() {
   useEffect(() => {
     Promise.all([
       invoke('check_install_state'),
-      invoke('read_config'),
+      invoke('get_config'),
     ]).then(([state, cfg]) => {
       setMode(state);
       setConfig(cfg);
     });
   }, []);
 
+  // The relay rejected our token: back to Login
+  useEffect(() => {
+    const unlisten = listen('session_expired', () => {
+      setConfig((cfg) => ({ ...cfg, needs_relogin: true }));
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
   if (mode === null) {
     return <div className="loading">Loading...</div>;
   }
@@ -72,7 +83,7 @@ export default function App() {
       <Complete
         key="done"
         onFinish={() => {
-          invoke('read_config').then(setConfig);
+          invoke('get_config').then(setConfig);
           setMode('app');
         }}
       />,
@@ -93,12 +104,14 @@ export default function App() {
   }
 
   // App mode
-  if (!config?.auth_token) {
+  if (!config?.has_auth_token || config?.needs_relogin) {
     return (
       <div className="app">
         <div className="logo">
           <h1>Remote Clauding</h1>
-          <div className="subtitle">Sign in to continue</div>
+          <div className="subtitle">
+            {config?.needs_relogin ? 'Your session expired. Sign in again' : 'Sign in to continue'}
+          </div>
         </div>
         <Login onLogin={(cfg) => setConfig(cfg)} />
       </div>
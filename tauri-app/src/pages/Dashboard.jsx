@@ -1,5 +1,6 @@
 import { useState, useEffect, useRef } from 'react';
 import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
 
 const STATUS_DISPLAY = {
   approved: { label: 'Approved', dot: 'green' },
@@ -10,6 +11,7 @@ const STATUS_DISPLAY = {
 
 export default function Dashboard({ config, onLogout }) {
   const [agentRunning, setAgentRunning] = useState(null);
+  const [agentStatus, setAgentStatus] = useState(null);
   const [relayReachable, setRelayReachable] = useState(null);
   const [accountStatus, setAccountStatus] = useState(null); // 'approved' | 'pending' | 'rejected' | null
   const [actionLoading, setActionLoading] = useState('');
@@ -18,10 +20,11 @@ export default function Dashboard({ config, onLogout }) {
   const checkStatus = async () => {
     try {
       const [agent, relay] = await Promise.all([
-        invoke('check_agent_health'),
+        invoke('agent_status'),
         invoke('check_relay_health'),
       ]);
-      setAgentRunning(agent.running);
+      setAgentStatus(agent);
+      setAgentRunning(agent.http_reachable);
       setRelayReachable(relay.running);
     } catch {
       // Ignore check errors
@@ -40,23 +43,33 @@ export default function Dashboard({ config, onLogout }) {
 
   useEffect(() => {
     checkStatus();
-    checkAccountStatus();
+    if (accountStatus === null) checkAccountStatus();
     interval.current = setInterval(() => {
       checkStatus();
-      if (accountStatus !== 'approved') checkAccountStatus();
+      // Pending is watched by the backend; only retry failed checks here
+      if (accountStatus === 'error') checkAccountStatus();
     }, 5000);
     return () => clearInterval(interval.current);
   }, [accountStatus]);
 
+  useEffect(() => {
+    if (accountStatus !== 'pending') return;
+    invoke('watch_account_status').catch(() => {});
+    const unlisten = listen('account_status_changed', (event) => {
+      setAccountStatus(event.payload.status);
+      checkStatus();
+    });
+    return () => { unlisten.then((fn) => fn()); };
+  }, [accountStatus]);
+
   const handleStartAgent = async () => {
     setActionLoading('start');
     try {
-      await invoke('start_agent');
-      await new Promise((r) => setTimeout(r, 2500));
-      await checkStatus();
-    } catch {
-      // Ignore
+      await invoke('start_agent', { wait: true });
+    } catch (err) {
+      console.error('start_agent failed:', err);
     }
+    await checkStatus();
     setActionLoading('');
   };
 
@@ -115,6 +128,16 @@ export default function Dashboard({ config, onLogout }) {
         </div>
       </div>
 
+      {agentStatus?.last_error && (
+        <div className="status-notice rejected">
+          <div className="status-notice-icon">&#x26A0;</div>
+          <div className="status-notice-text">
+            <strong>Agent Problem</strong>
+            <p>{agentStatus.last_error}</p>
+          </div>
+        </div>
+      )}
+
       {accountStatus === 'pending' && (
         <div className="status-notice pending">
           <div className="status-notice-icon">&#x23F3;</div>
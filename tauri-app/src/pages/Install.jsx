@@ -20,6 +20,7 @@ export default function Install({ data, onComplete }) {
   const [logs, setLogs] = useState('');
   const [showLog, setShowLog] = useState(false);
   const [error, setError] = useState('');
+  const [currentTask, setCurrentTask] = useState(null);
   const running = useRef(false);
 
   const updateStep = (id, updates) => {
@@ -52,10 +53,19 @@ export default function Install({ data, onComplete }) {
       }
     });
 
+    const unlistenStarted = listen('task_started', (event) => {
+      setCurrentTask(event.payload.id);
+    });
+    const unlistenFinished = listen('task_finished', () => {
+      setCurrentTask(null);
+    });
+
     runInstall();
 
     return () => {
       unlistenPromise.then((fn) => fn());
+      unlistenStarted.then((fn) => fn());
+      unlistenFinished.then((fn) => fn());
     };
   }, []);
 
@@ -140,6 +150,16 @@ export default function Install({ data, onComplete }) {
         ))}
       </ul>
 
+      {currentTask && (
+        <button
+          className="btn btn-secondary"
+          style={{ marginTop: 16 }}
+          onClick={() => invoke('cancel_task', { taskId: currentTask }).catch(() => {})}
+        >
+          Cancel
+        </button>
+      )}
+
       {error && <div className="error" style={{ marginTop: 16 }}>{error}</div>}
 
       <div className="log-toggle" onClick={() => setShowLog(!showLog)}>
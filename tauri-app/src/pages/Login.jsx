@@ -1,5 +1,6 @@
-import { useState } from 'react';
+import { useState, useEffect } from 'react';
 import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
 
 export default function Login({ onLogin }) {
   const [email, setEmail] = useState('');
@@ -7,6 +8,38 @@ export default function Login({ onLogin }) {
   const [error, setError] = useState('');
   const [accountStatus, setAccountStatus] = useState(null); // 'pending' | 'rejected' | null
   const [loading, setLoading] = useState(false);
+  const [browserLogin, setBrowserLogin] = useState(null); // { taskId, user_code, verification_uri }
+
+  useEffect(() => {
+    const unlistenStarted = listen('task_started', (event) => {
+      if (event.payload.kind === 'browser_login') {
+        setBrowserLogin((prev) => ({ ...prev, taskId: event.payload.id }));
+      }
+    });
+    const unlistenCode = listen('device_login_code', (event) => {
+      setBrowserLogin((prev) => ({ ...prev, ...event.payload }));
+    });
+    return () => {
+      unlistenStarted.then((fn) => fn());
+      unlistenCode.then((fn) => fn());
+    };
+  }, []);
+
+  const handleBrowserLogin = async () => {
+    setError('');
+    setAccountStatus(null);
+    setBrowserLogin({});
+    try {
+      await invoke('login_with_browser');
+      // The email comes from the relay, so take it from the saved config
+      onLogin(await invoke('get_config'));
+    } catch (err) {
+      const message = err?.message ?? String(err);
+      if (message !== 'Cancelled') setError(message);
+    } finally {
+      setBrowserLogin(null);
+    }
+  };
 
   const handleSubmit = async (e) => {
     e.preventDefault();
@@ -23,24 +56,20 @@ export default function Login({ onLogin }) {
       const result = await invoke('login', { email, password });
 
       if (result.auth_token) {
-        // Auto-start agent after login
-        try {
-          await invoke('start_agent');
-        } catch {
-          // Agent start is best-effort
-        }
-        onLogin({ auth_token: result.auth_token, email });
+        // The login command restarts the agent with the new token
+        onLogin({ has_auth_token: true, email });
       } else {
         setError('Login failed: no token received.');
       }
     } catch (err) {
-      const errStr = String(err).toLowerCase();
-      if (errStr.includes('pending')) {
+      const message = err?.message ?? String(err);
+      const errStr = message.toLowerCase();
+      if (err?.code === 'server' && errStr.includes('pending')) {
         setAccountStatus('pending');
-      } else if (errStr.includes('rejected')) {
+      } else if (err?.code === 'server' && errStr.includes('rejected')) {
         setAccountStatus('rejected');
       } else {
-        setError(String(err));
+        setError(message);
       }
     } finally {
       setLoading(false);
@@ -88,10 +117,33 @@ export default function Login({ onLogin }) {
             placeholder="Password"
           />
         </div>
-        <button className="btn btn-primary" disabled={loading}>
+        <button className="btn btn-primary" disabled={loading || browserLogin}>
           {loading ? 'Logging in...' : 'Log In'}
         </button>
       </form>
+      {browserLogin ? (
+        <div className="status-notice pending" style={{ marginTop: 16 }}>
+          <div className="status-notice-text">
+            <strong>Waiting for browser sign-in</strong>
+            {browserLogin.user_code && (
+              <p>
+                Confirm the code <strong>{browserLogin.user_code}</strong> at{' '}
+                {browserLogin.verification_uri}
+              </p>
+            )}
+            <span
+              className="link"
+              onClick={() => browserLogin.taskId && invoke('cancel_task', { taskId: browserLogin.taskId }).catch(() => {})}
+            >
+              Cancel
+            </span>
+          </div>
+        </div>
+      ) : (
+        <button className="btn btn-secondary" style={{ marginTop: 12 }} onClick={handleBrowserLogin} disabled={loading}>
+          Sign in with browser
+        </button>
+      )}
       {accountStatus === 'pending' && (
         <p className="text-center" style={{ marginTop: 16 }}>
           <span className="link" onClick={handleSubmit}>Check again</span>
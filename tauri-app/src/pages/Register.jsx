@@ -42,7 +42,7 @@ export default function Register({ data, onNext, onSkip }) {
         onNext({ ...data, email });
       }
     } catch (err) {
-      setError(String(err));
+      setError(err?.message ?? String(err));
     } finally {
       setLoading(false);
     }
@@ -64,11 +64,12 @@ export default function Register({ data, onNext, onSkip }) {
         onNext({ ...data, email });
       }
     } catch (err) {
-      const errStr = String(err).toLowerCase();
+      const message = err?.message ?? String(err);
+      const errStr = message.toLowerCase();
       if (errStr.includes('already verified') || errStr.includes('already been verified')) {
         setView('pending');
       } else {
-        setError(String(err));
+        setError(message);
       }
     } finally {
       setLoading(false);
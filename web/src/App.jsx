@@ -21,6 +21,53 @@ function getPairToken() {
   return localStorage.getItem('session_token') || null;
 }
 
+function getPairCode() {
+  return new URLSearchParams(window.location.search).get('pair');
+}
+
+// Exchanges a one-time pairing code from the desktop app's QR code for an
+// account token, then reloads without the code in the URL
+async function redeemPairCode(code) {
+  const resp = await fetch('/api/auth/pairing-code/redeem', {
+    method: 'POST',
+    headers: { 'Content-Type': 'application/json' },
+    body: JSON.stringify({ code }),
+  });
+  const data = await resp.json().catch(() => ({}));
+  if (!resp.ok || !data.auth_token) {
+    throw new Error(data.error || 'Pairing failed');
+  }
+  localStorage.setItem('auth_token', data.auth_token);
+  // An account token sees all sessions; a leftover session token would narrow it
+  localStorage.removeItem('session_token');
+}
+
+function PairPage({ code }) {
+  const [error, setError] = useState(null);
+
+  useEffect(() => {
+    redeemPairCode(code)
+      .then(() => window.location.replace(window.location.pathname))
+      .catch((err) => setError(err.message));
+  }, [code]);
+
+  return (
+    <div className="app">
+      <div className="install-page">
+        <h1 className="install-title">Remote Clauding</h1>
+        {error ? (
+          <>
+            <p className="install-subtitle">{error}</p>
+            <p className="install-hint">Show a new QR code in the desktop app and scan it again.</p>
+          </>
+        ) : (
+          <p className="install-subtitle">Pairing with your desktop...</p>
+        )}
+      </div>
+    </div>
+  );
+}
+
 function InstallPage() {
   const isIOS = /iPad|iPhone|iPod/.test(navigator.userAgent);
 
@@ -68,6 +115,10 @@ function MainApp() {
     window.location.reload();
   };
 
+  const handlePairCode = (code) => {
+    window.location.assign(`${window.location.pathname}?pair=${encodeURIComponent(code)}`);
+  };
+
   const openSession = (sessionId) => {
     relay.subscribeSession(sessionId);
     setView('session');
@@ -102,6 +153,7 @@ function MainApp() {
       {showScanner && (
         <QRScanner
           onScan={handleScan}
+          onPairCode={handlePairCode}
           onClose={() => setShowScanner(false)}
         />
       )}
@@ -131,6 +183,11 @@ function MainApp() {
 }
 
 export default function App() {
+  // Pairing works from the browser too, before the app is installed
+  const pairCode = getPairCode();
+  if (pairCode) {
+    return <PairPage code={pairCode} />;
+  }
   if (!STANDALONE) {
     return <InstallPage />;
   }
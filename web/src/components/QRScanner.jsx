@@ -1,9 +1,11 @@
 import React, { useEffect, useRef } from 'react';
 import { Html5Qrcode } from 'html5-qrcode';
 
-export default function QRScanner({ onScan, onClose }) {
+export default function QRScanner({ onScan, onPairCode, onClose }) {
   const onScanRef = useRef(onScan);
   onScanRef.current = onScan;
+  const onPairCodeRef = useRef(onPairCode);
+  onPairCodeRef.current = onPairCode;
   const scannedRef = useRef(false);
 
   useEffect(() => {
@@ -21,6 +23,14 @@ export default function QRScanner({ onScan, onClose }) {
             const token = decodeURIComponent(match[1]);
             scanner.stop().catch(() => {});
             onScanRef.current(token);
+            return;
+          }
+          // Account pairing QR from the desktop app: <relay>/?pair=<code>
+          const pair = decodedText.match(/[?&]pair=([^&#]+)/);
+          if (pair && onPairCodeRef.current) {
+            scannedRef.current = true;
+            scanner.stop().catch(() => {});
+            onPairCodeRef.current(decodeURIComponent(pair[1]));
           }
         }
       )